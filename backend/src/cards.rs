@@ -1,3 +1,4 @@
+use rand::prelude::*;
 use crate::game::{
     Card,
     CardEffect,
@@ -19,12 +20,21 @@ use crate::game::{
 /// The CardRegistry produces the card for the games depending on the age and the number of players.
 #[derive(Debug, Clone)]
 pub struct CardRegistry {
-    cards: [Vec<Card>; 3],
+    cards: [Vec<CardDefinition>; 3],
+    guilds: Vec<Card>,
 }
 
 impl CardRegistry {
-    pub fn generate_cards(&self, age: usize, player_count: usize) -> Vec<Card> {
-        self.cards[age][0..(player_count*7)].to_vec()
+    /// Generate the cards of some age. In the third age, `player_count + 2` guilds are randomly chosen.
+    pub fn generate_cards<R: Rng + ?Sized>(&self, age: usize, player_count: usize, rng: &mut R) -> Vec<Card> {
+        let mut cards: Vec<_> = self.cards[age].iter()
+            .filter(|definition| definition.min_players <= player_count)
+            .map(|definition| definition.card.clone())
+            .collect();
+        if age == 2 {
+            cards.extend(self.guilds.choose_multiple(rng, player_count + 2).cloned());
+        }
+        cards
     }
 
     pub fn new() -> CardRegistry {
@@ -32,43 +42,323 @@ impl CardRegistry {
         Self {
             cards: [
                 // AGE ONE
-                vec![
+                [
+                    // ** THREE PLAYERS
+                    for_players(3, vec![
+                        // *** BROWN CARDS
+                        CardBuilder::new("Chantier", COLOR_BROWN, Resources([0, 1, 0, 0, 0, 0, 0])),
+                        CardBuilder::new("Cavité", COLOR_BROWN, Resources([0, 0, 0, 1, 0, 0, 0])),
+                        CardBuilder::new("Bassin argileux", COLOR_BROWN, Resources([1, 0, 0, 0, 0, 0, 0])),
+                        CardBuilder::new("Filon", COLOR_BROWN, Resources([0, 0, 1, 0, 0, 0, 0])),
+                        CardBuilder::new("Fosse argileuse", COLOR_BROWN, Resources([1, 0, 1, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Exploitation forestière", COLOR_BROWN, Resources([0, 1, 0, 1, 0, 0, 0])).with_cost_gold(1),
+                        // *** GRAY CARDS
+                        CardBuilder::new("Métier à tisser", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 0, 1])),
+                        CardBuilder::new("Verrerie", COLOR_GRAY, Resources([0, 0, 0, 0, 1, 0, 0])),
+                        CardBuilder::new("Presse", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 1, 0])),
+                        // *** BLUE CARDS
+                        CardBuilder::new("Bains", COLOR_BLUE, Points(3)).with_cost_stone(1)
+                            .with_chaining_target("Aqueduc"),
+                        CardBuilder::new("Autel", COLOR_BLUE, Points(2))
+                            .with_chaining_target("Temple"),
+                        CardBuilder::new("Théâtre", COLOR_BLUE, Points(2))
+                            .with_chaining_target("Statue"),
+                        // *** YELLOW CARDS
+                        CardBuilder::new("Comptoir est", COLOR_YELLOW, RawMaterialsCost(RIGHT_PLAYER))
+                            .with_chaining_target("Forum"),
+                        CardBuilder::new("Comptoir ouest", COLOR_YELLOW, RawMaterialsCost(LEFT_PLAYER))
+                            .with_chaining_target("Forum"),
+                        CardBuilder::new("Marché", COLOR_YELLOW, ManufacturedProductsCost)
+                            .with_chaining_target("Caravansérail"),
+                        // *** RED CARDS
+                        CardBuilder::new("Palissade", COLOR_RED, Shields(1)).with_cost_wood(1),
+                        CardBuilder::new("Caserne", COLOR_RED, Shields(1)).with_cost_ore(1),
+                        CardBuilder::new("Tour de garde", COLOR_RED, Shields(1)).with_cost_clay(1),
+                        // *** GREEN CARDS
+                        CardBuilder::new("Officine", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_loom(1)
+                            .with_chaining_target("Écuries").with_chaining_target("Dispensaire"),
+                        CardBuilder::new("Atelier", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_glass(1)
+                            .with_chaining_target("Champ de tir").with_chaining_target("Laboratoire"),
+                        CardBuilder::new("Scriptorium", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_papyrus(1)
+                            .with_chaining_target("Tribunal").with_chaining_target("Bibliothèque"),
+                    ]),
+                    // ** FOUR PLAYERS
+                    for_players(4, vec![
+                        CardBuilder::new("Chantier", COLOR_BROWN, Resources([0, 1, 0, 0, 0, 0, 0])),
+                        CardBuilder::new("Filon", COLOR_BROWN, Resources([0, 0, 1, 0, 0, 0, 0])),
+                        CardBuilder::new("Excavation", COLOR_BROWN, Resources([1, 0, 0, 1, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Prêteur sur gages", COLOR_BLUE, Points(3)),
+                        CardBuilder::new("Taverne", COLOR_YELLOW, Gold(5)),
+                        CardBuilder::new("Tour de garde", COLOR_RED, Shields(1)).with_cost_clay(1),
+                        CardBuilder::new("Scriptorium", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_papyrus(1)
+                            .with_chaining_target("Tribunal").with_chaining_target("Bibliothèque"),
+                    ]),
+                    // ** FIVE PLAYERS
+                    for_players(5, vec![
+                        CardBuilder::new("Cavité", COLOR_BROWN, Resources([0, 0, 0, 1, 0, 0, 0])),
+                        CardBuilder::new("Bassin argileux", COLOR_BROWN, Resources([1, 0, 0, 0, 0, 0, 0])),
+                        CardBuilder::new("Gisement", COLOR_BROWN, Resources([0, 1, 1, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Autel", COLOR_BLUE, Points(2))
+                            .with_chaining_target("Temple"),
+                        CardBuilder::new("Taverne", COLOR_YELLOW, Gold(5)),
+                        CardBuilder::new("Caserne", COLOR_RED, Shields(1)).with_cost_ore(1),
+                        CardBuilder::new("Officine", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_loom(1)
+                            .with_chaining_target("Écuries").with_chaining_target("Dispensaire"),
+                    ]),
+                    // ** SIX PLAYERS
+                    for_players(6, vec![
+                        CardBuilder::new("Friche", COLOR_BROWN, Resources([1, 1, 0, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Mine", COLOR_BROWN, Resources([0, 0, 1, 1, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Métier à tisser", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 0, 1])),
+                        CardBuilder::new("Verrerie", COLOR_GRAY, Resources([0, 0, 0, 0, 1, 0, 0])),
+                        CardBuilder::new("Presse", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 1, 0])),
+                        CardBuilder::new("Théâtre", COLOR_BLUE, Points(2))
+                            .with_chaining_target("Statue"),
+                        CardBuilder::new("Marché", COLOR_YELLOW, ManufacturedProductsCost)
+                            .with_chaining_target("Caravansérail"),
+                    ]),
+                    // ** SEVEN PLAYERS
+                    for_players(7, vec![
+                        CardBuilder::new("Prêteur sur gages", COLOR_BLUE, Points(3)),
+                        CardBuilder::new("Bains", COLOR_BLUE, Points(3)).with_cost_stone(1)
+                            .with_chaining_target("Aqueduc"),
+                        CardBuilder::new("Taverne", COLOR_YELLOW, Gold(5)),
+                        CardBuilder::new("Comptoir est", COLOR_YELLOW, RawMaterialsCost(RIGHT_PLAYER))
+                            .with_chaining_target("Forum"),
+                        CardBuilder::new("Comptoir ouest", COLOR_YELLOW, RawMaterialsCost(LEFT_PLAYER))
+                            .with_chaining_target("Forum"),
+                        CardBuilder::new("Palissade", COLOR_RED, Shields(1)).with_cost_wood(1),
+                        CardBuilder::new("Atelier", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_glass(1)
+                            .with_chaining_target("Champ de tir").with_chaining_target("Laboratoire"),
+                    ]),
+                ].concat(),
+                // AGE TWO
+                [
+                    // ** THREE PLAYERS
+                    for_players(3, vec![
+                        // *** BROWN CARDS
+                        CardBuilder::new("Scierie", COLOR_BROWN, Resources([0, 2, 0, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Carrière", COLOR_BROWN, Resources([0, 0, 0, 2, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Briqueterie", COLOR_BROWN, Resources([2, 0, 0, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Fonderie", COLOR_BROWN, Resources([0, 0, 2, 0, 0, 0, 0])).with_cost_gold(1),
+                        // *** GRAY CARDS
+                        CardBuilder::new("Métier à tisser", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 0, 1])),
+                        CardBuilder::new("Verrerie", COLOR_GRAY, Resources([0, 0, 0, 0, 1, 0, 0])),
+                        CardBuilder::new("Presse", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 1, 0])),
+                        // *** BLUE CARDS
+                        CardBuilder::new("Aqueduc", COLOR_BLUE, Points(5)).with_cost_stone(3)
+                            .with_chaining_source("Bains"),
+                        CardBuilder::new("Temple", COLOR_BLUE, Points(3)).with_cost_wood(1).with_cost_clay(1).with_cost_glass(1)
+                            .with_chaining_source("Autel").with_chaining_target("Panthéon"),
+                        CardBuilder::new("Statue", COLOR_BLUE, Points(4)).with_cost_ore(2).with_cost_wood(1)
+                            .with_chaining_source("Théâtre").with_chaining_target("Jardins"),
+                        CardBuilder::new("Tribunal", COLOR_BLUE, Points(4)).with_cost_clay(2).with_cost_loom(1)
+                            .with_chaining_source("Scriptorium"),
+                        // *** YELLOW CARDS
+                        CardBuilder::new("Forum", COLOR_YELLOW, PrivateResources([0, 0, 0, 0, 1, 1, 1])).with_cost_clay(2)
+                            .with_chaining_source("Comptoir est").with_chaining_source("Comptoir ouest").with_chaining_target("Port"),
+                        CardBuilder::new("Caravansérail", COLOR_YELLOW, PrivateResources([1, 1, 1, 1, 0, 0, 0])).with_cost_wood(2)
+                            .with_chaining_source("Marché").with_chaining_target("Phare"),
+                        CardBuilder::new("Vignoble", COLOR_YELLOW, GoldPerCard(COLOR_BROWN, 1)),
+                        // *** RED CARDS
+                        CardBuilder::new("Muraille", COLOR_RED, Shields(2)).with_cost_stone(3)
+                            .with_chaining_target("Fortifications"),
+                        CardBuilder::new("Écuries", COLOR_RED, Shields(2)).with_cost_ore(1).with_cost_clay(1).with_cost_wood(1)
+                            .with_chaining_source("Officine"),
+                        CardBuilder::new("Champ de tir", COLOR_RED, Shields(2)).with_cost_wood(2).with_cost_ore(1)
+                            .with_chaining_source("Atelier"),
+                        // *** GREEN CARDS
+                        CardBuilder::new("Dispensaire", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_ore(2).with_cost_glass(1)
+                            .with_chaining_source("Officine").with_chaining_target("Loge").with_chaining_target("Arène"),
+                        CardBuilder::new("Laboratoire", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_clay(2).with_cost_papyrus(1)
+                            .with_chaining_source("Atelier").with_chaining_target("Atelier de siège").with_chaining_target("Observatoire"),
+                        CardBuilder::new("Bibliothèque", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_stone(2).with_cost_loom(1)
+                            .with_chaining_source("Scriptorium").with_chaining_target("Sénat").with_chaining_target("Université"),
+                        CardBuilder::new("École", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_wood(1).with_cost_papyrus(1)
+                            .with_chaining_target("Académie").with_chaining_target("Étude"),
+                    ]),
+                    // ** FOUR PLAYERS
+                    for_players(4, vec![
+                        CardBuilder::new("Scierie", COLOR_BROWN, Resources([0, 2, 0, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Carrière", COLOR_BROWN, Resources([0, 0, 0, 2, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Briqueterie", COLOR_BROWN, Resources([2, 0, 0, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Fonderie", COLOR_BROWN, Resources([0, 0, 2, 0, 0, 0, 0])).with_cost_gold(1),
+                        CardBuilder::new("Bazar", COLOR_YELLOW, GoldPerCard(COLOR_GRAY, 2)),
+                        CardBuilder::new("Place d'armes", COLOR_RED, Shields(2)).with_cost_ore(2).with_cost_wood(1)
+                            .with_chaining_target("Cirque"),
+                        CardBuilder::new("Dispensaire", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_ore(2).with_cost_glass(1)
+                            .with_chaining_source("Officine").with_chaining_target("Loge").with_chaining_target("Arène"),
+                    ]),
+                    // ** FIVE PLAYERS
+                    for_players(5, vec![
+                        CardBuilder::new("Métier à tisser", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 0, 1])),
+                        CardBuilder::new("Verrerie", COLOR_GRAY, Resources([0, 0, 0, 0, 1, 0, 0])),
+                        CardBuilder::new("Presse", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 1, 0])),
+                        CardBuilder::new("Tribunal", COLOR_BLUE, Points(4)).with_cost_clay(2).with_cost_loom(1)
+                            .with_chaining_source("Scriptorium"),
+                        CardBuilder::new("Caravansérail", COLOR_YELLOW, PrivateResources([1, 1, 1, 1, 0, 0, 0])).with_cost_wood(2)
+                            .with_chaining_source("Marché").with_chaining_target("Phare"),
+                        CardBuilder::new("Écuries", COLOR_RED, Shields(2)).with_cost_ore(1).with_cost_clay(1).with_cost_wood(1)
+                            .with_chaining_source("Officine"),
+                        CardBuilder::new("Laboratoire", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_clay(2).with_cost_papyrus(1)
+                            .with_chaining_source("Atelier").with_chaining_target("Atelier de siège").with_chaining_target("Observatoire"),
+                    ]),
+                    // ** SIX PLAYERS
+                    for_players(6, vec![
+                        CardBuilder::new("Temple", COLOR_BLUE, Points(3)).with_cost_wood(1).with_cost_clay(1).with_cost_glass(1)
+                            .with_chaining_source("Autel").with_chaining_target("Panthéon"),
+                        CardBuilder::new("Forum", COLOR_YELLOW, PrivateResources([0, 0, 0, 0, 1, 1, 1])).with_cost_clay(2)
+                            .with_chaining_source("Comptoir est").with_chaining_source("Comptoir ouest").with_chaining_target("Port"),
+                        CardBuilder::new("Caravansérail", COLOR_YELLOW, PrivateResources([1, 1, 1, 1, 0, 0, 0])).with_cost_wood(2)
+                            .with_chaining_source("Marché").with_chaining_target("Phare"),
+                        CardBuilder::new("Vignoble", COLOR_YELLOW, GoldPerCard(COLOR_BROWN, 1)),
+                        CardBuilder::new("Place d'armes", COLOR_RED, Shields(2)).with_cost_ore(2).with_cost_wood(1)
+                            .with_chaining_target("Cirque"),
+                        CardBuilder::new("Champ de tir", COLOR_RED, Shields(2)).with_cost_wood(2).with_cost_ore(1)
+                            .with_chaining_source("Atelier"),
+                        CardBuilder::new("Bibliothèque", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_stone(2).with_cost_loom(1)
+                            .with_chaining_source("Scriptorium").with_chaining_target("Sénat").with_chaining_target("Université"),
+                    ]),
+                    // ** SEVEN PLAYERS
+                    for_players(7, vec![
+                        CardBuilder::new("Aqueduc", COLOR_BLUE, Points(5)).with_cost_stone(3)
+                            .with_chaining_source("Bains"),
+                        CardBuilder::new("Statue", COLOR_BLUE, Points(4)).with_cost_ore(2).with_cost_wood(1)
+                            .with_chaining_source("Théâtre").with_chaining_target("Jardins"),
+                        CardBuilder::new("Forum", COLOR_YELLOW, PrivateResources([0, 0, 0, 0, 1, 1, 1])).with_cost_clay(2)
+                            .with_chaining_source("Comptoir est").with_chaining_source("Comptoir ouest").with_chaining_target("Port"),
+                        CardBuilder::new("Bazar", COLOR_YELLOW, GoldPerCard(COLOR_GRAY, 2)),
+                        CardBuilder::new("Muraille", COLOR_RED, Shields(2)).with_cost_stone(3)
+                            .with_chaining_target("Fortifications"),
+                        CardBuilder::new("Place d'armes", COLOR_RED, Shields(2)).with_cost_ore(2).with_cost_wood(1)
+                            .with_chaining_target("Cirque"),
+                        CardBuilder::new("École", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_wood(1).with_cost_papyrus(1)
+                            .with_chaining_target("Académie").with_chaining_target("Étude"),
+                    ]),
+                ].concat(),
+                // AGE THREE
+                [
                     // ** THREE PLAYERS
-                    // *** BROWN CARDS
-                    CardBuilder::new("Chantier", COLOR_BROWN, Resources([0, 1, 0, 0, 0, 0, 0])),
-                    CardBuilder::new("Cavité", COLOR_BROWN, Resources([0, 0, 0, 1, 0, 0, 0])),
-                    CardBuilder::new("Bassin argileux", COLOR_BROWN, Resources([1, 0, 0, 0, 0, 0, 0])),
-                    CardBuilder::new("Filon", COLOR_BROWN, Resources([0, 0, 1, 0, 0, 0, 0])),
-                    CardBuilder::new("Fosse argileuse", COLOR_BROWN, Resources([1, 0, 1, 0, 0, 0, 0])).with_cost_gold(1),
-                    CardBuilder::new("Exploitation forestière", COLOR_BROWN, Resources([0, 1, 0, 1, 0, 0, 0])).with_cost_gold(1),
-                    // *** GRAY CARDS
-                    CardBuilder::new("Métier à tisser", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 0, 1])),
-                    CardBuilder::new("Verrerie", COLOR_GRAY, Resources([0, 0, 0, 0, 1, 0, 0])),
-                    CardBuilder::new("Presse", COLOR_GRAY, Resources([0, 0, 0, 0, 0, 1, 0])),
-                    // *** BLUE CARDS
-                    CardBuilder::new("Bains", COLOR_BLUE, Points(3)).with_cost_stone(1),
-                    CardBuilder::new("Autel", COLOR_BLUE, Points(2)),
-                    CardBuilder::new("Théâtre", COLOR_BLUE, Points(2)),
-                    // *** YELLOW CARDS
-                    CardBuilder::new("Comptoir est", COLOR_YELLOW, RawMaterialsCost(RIGHT_PLAYER)),
-                    CardBuilder::new("Comptoir ouest", COLOR_YELLOW, RawMaterialsCost(LEFT_PLAYER)),
-                    CardBuilder::new("Marché", COLOR_YELLOW, ManufacturedProductsCost),
-                    // *** RED CARDS
-                    CardBuilder::new("Palissade", COLOR_RED, Shields(1)).with_cost_wood(1),
-                    CardBuilder::new("Caserne", COLOR_RED, Shields(1)).with_cost_ore(1),
-                    CardBuilder::new("Tour de garde", COLOR_RED, Shields(1)).with_cost_clay(1),
-                    // *** GREEN CARDS
-                    CardBuilder::new("Officine", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_loom(1),
-                    CardBuilder::new("Atelier", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_glass(1),
-                    CardBuilder::new("Scriptorium", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_papyrus(1),
-                ].into_iter().map(|cb| cb.build()).collect(),
-                vec![],
-                vec![],
+                    for_players(3, vec![
+                        // *** BLUE CARDS
+                        CardBuilder::new("Panthéon", COLOR_BLUE, Points(7)).with_cost_clay(2).with_cost_ore(1).with_cost_glass(1).with_cost_papyrus(1).with_cost_loom(1)
+                            .with_chaining_source("Temple"),
+                        CardBuilder::new("Jardins", COLOR_BLUE, Points(5)).with_cost_clay(2).with_cost_wood(1)
+                            .with_chaining_source("Statue"),
+                        CardBuilder::new("Hôtel de ville", COLOR_BLUE, Points(6)).with_cost_stone(2).with_cost_ore(1).with_cost_glass(1),
+                        CardBuilder::new("Palais", COLOR_BLUE, Points(8)).with_cost([1, 1, 1, 1, 1, 1, 1]),
+                        CardBuilder::new("Sénat", COLOR_BLUE, Points(6)).with_cost_wood(2).with_cost_stone(1).with_cost_ore(1)
+                            .with_chaining_source("Bibliothèque"),
+                        // *** YELLOW CARDS
+                        CardBuilder::new("Port", COLOR_YELLOW, GoldAndPointsPerCard(COLOR_BROWN, 1, 1)).with_cost_wood(1).with_cost_ore(1).with_cost_loom(1)
+                            .with_chaining_source("Forum"),
+                        CardBuilder::new("Phare", COLOR_YELLOW, GoldAndPointsPerCard(COLOR_YELLOW, 1, 1)).with_cost_stone(1).with_cost_glass(1)
+                            .with_chaining_source("Caravansérail"),
+                        CardBuilder::new("Arène", COLOR_YELLOW, GoldAndPointsPerWonderStage(3, 1)).with_cost_stone(2).with_cost_ore(1)
+                            .with_chaining_source("Dispensaire"),
+                        // *** RED CARDS
+                        CardBuilder::new("Fortifications", COLOR_RED, Shields(3)).with_cost_ore(3).with_cost_stone(1)
+                            .with_chaining_source("Muraille"),
+                        CardBuilder::new("Arsenal", COLOR_RED, Shields(3)).with_cost_wood(2).with_cost_ore(1).with_cost_loom(1),
+                        CardBuilder::new("Atelier de siège", COLOR_RED, Shields(3)).with_cost_clay(3).with_cost_wood(1)
+                            .with_chaining_source("Laboratoire"),
+                        // *** GREEN CARDS
+                        CardBuilder::new("Loge", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_clay(2).with_cost_papyrus(1).with_cost_loom(1)
+                            .with_chaining_source("Dispensaire"),
+                        CardBuilder::new("Observatoire", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_ore(2).with_cost_glass(1).with_cost_loom(1)
+                            .with_chaining_source("Laboratoire"),
+                        CardBuilder::new("Université", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_wood(2).with_cost_papyrus(1).with_cost_glass(1)
+                            .with_chaining_source("Bibliothèque"),
+                        CardBuilder::new("Académie", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_stone(3).with_cost_glass(1)
+                            .with_chaining_source("École"),
+                        CardBuilder::new("Étude", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_wood(1).with_cost_papyrus(1).with_cost_loom(1)
+                            .with_chaining_source("École"),
+                    ]),
+                    // ** FOUR PLAYERS
+                    for_players(4, vec![
+                        CardBuilder::new("Jardins", COLOR_BLUE, Points(5)).with_cost_clay(2).with_cost_wood(1)
+                            .with_chaining_source("Statue"),
+                        CardBuilder::new("Port", COLOR_YELLOW, GoldAndPointsPerCard(COLOR_BROWN, 1, 1)).with_cost_wood(1).with_cost_ore(1).with_cost_loom(1)
+                            .with_chaining_source("Forum"),
+                        CardBuilder::new("Chambre de commerce", COLOR_YELLOW, GoldAndPointsPerCard(COLOR_GRAY, 2, 2)).with_cost_clay(2).with_cost_papyrus(1),
+                        CardBuilder::new("Cirque", COLOR_RED, Shields(3)).with_cost_stone(3).with_cost_ore(1)
+                            .with_chaining_source("Place d'armes"),
+                        CardBuilder::new("Arsenal", COLOR_RED, Shields(3)).with_cost_wood(2).with_cost_ore(1).with_cost_loom(1),
+                        CardBuilder::new("Université", COLOR_GREEN, Science(SCIENCE_TABLET)).with_cost_wood(2).with_cost_papyrus(1).with_cost_glass(1)
+                            .with_chaining_source("Bibliothèque"),
+                    ]),
+                    // ** FIVE PLAYERS
+                    for_players(5, vec![
+                        CardBuilder::new("Hôtel de ville", COLOR_BLUE, Points(6)).with_cost_stone(2).with_cost_ore(1).with_cost_glass(1),
+                        CardBuilder::new("Sénat", COLOR_BLUE, Points(6)).with_cost_wood(2).with_cost_stone(1).with_cost_ore(1)
+                            .with_chaining_source("Bibliothèque"),
+                        CardBuilder::new("Arène", COLOR_YELLOW, GoldAndPointsPerWonderStage(3, 1)).with_cost_stone(2).with_cost_ore(1)
+                            .with_chaining_source("Dispensaire"),
+                        CardBuilder::new("Cirque", COLOR_RED, Shields(3)).with_cost_stone(3).with_cost_ore(1)
+                            .with_chaining_source("Place d'armes"),
+                        CardBuilder::new("Atelier de siège", COLOR_RED, Shields(3)).with_cost_clay(3).with_cost_wood(1)
+                            .with_chaining_source("Laboratoire"),
+                        CardBuilder::new("Étude", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_wood(1).with_cost_papyrus(1).with_cost_loom(1)
+                            .with_chaining_source("École"),
+                    ]),
+                    // ** SIX PLAYERS
+                    for_players(6, vec![
+                        CardBuilder::new("Panthéon", COLOR_BLUE, Points(7)).with_cost_clay(2).with_cost_ore(1).with_cost_glass(1).with_cost_papyrus(1).with_cost_loom(1)
+                            .with_chaining_source("Temple"),
+                        CardBuilder::new("Hôtel de ville", COLOR_BLUE, Points(6)).with_cost_stone(2).with_cost_ore(1).with_cost_glass(1),
+                        CardBuilder::new("Phare", COLOR_YELLOW, GoldAndPointsPerCard(COLOR_YELLOW, 1, 1)).with_cost_stone(1).with_cost_glass(1)
+                            .with_chaining_source("Caravansérail"),
+                        CardBuilder::new("Chambre de commerce", COLOR_YELLOW, GoldAndPointsPerCard(COLOR_GRAY, 2, 2)).with_cost_clay(2).with_cost_papyrus(1),
+                        CardBuilder::new("Cirque", COLOR_RED, Shields(3)).with_cost_stone(3).with_cost_ore(1)
+                            .with_chaining_source("Place d'armes"),
+                        CardBuilder::new("Loge", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_clay(2).with_cost_papyrus(1).with_cost_loom(1)
+                            .with_chaining_source("Dispensaire"),
+                    ]),
+                    // ** SEVEN PLAYERS
+                    for_players(7, vec![
+                        CardBuilder::new("Palais", COLOR_BLUE, Points(8)).with_cost([1, 1, 1, 1, 1, 1, 1]),
+                        CardBuilder::new("Arène", COLOR_YELLOW, GoldAndPointsPerWonderStage(3, 1)).with_cost_stone(2).with_cost_ore(1)
+                            .with_chaining_source("Dispensaire"),
+                        CardBuilder::new("Fortifications", COLOR_RED, Shields(3)).with_cost_ore(3).with_cost_stone(1)
+                            .with_chaining_source("Muraille"),
+                        CardBuilder::new("Arsenal", COLOR_RED, Shields(3)).with_cost_wood(2).with_cost_ore(1).with_cost_loom(1),
+                        CardBuilder::new("Observatoire", COLOR_GREEN, Science(SCIENCE_GEAR)).with_cost_ore(2).with_cost_glass(1).with_cost_loom(1)
+                            .with_chaining_source("Laboratoire"),
+                        CardBuilder::new("Académie", COLOR_GREEN, Science(SCIENCE_COMPASS)).with_cost_stone(3).with_cost_glass(1)
+                            .with_chaining_source("École"),
+                    ]),
+                ].concat(),
             ],
+            // GUILDS
+            guilds: vec![
+                CardBuilder::new("Guilde des travailleurs", COLOR_PURPLE, PointsPerNeighbourCard(COLOR_BROWN, 1)).with_cost_ore(2).with_cost_clay(1).with_cost_stone(1).with_cost_wood(1),
+                CardBuilder::new("Guilde des artisans", COLOR_PURPLE, PointsPerNeighbourCard(COLOR_GRAY, 2)).with_cost_ore(2).with_cost_stone(2),
+                CardBuilder::new("Guilde des commerçants", COLOR_PURPLE, PointsPerNeighbourCard(COLOR_YELLOW, 1)).with_cost_loom(1).with_cost_papyrus(1).with_cost_glass(1),
+                CardBuilder::new("Guilde des philosophes", COLOR_PURPLE, PointsPerNeighbourCard(COLOR_GREEN, 1)).with_cost_clay(3).with_cost_loom(1).with_cost_papyrus(1),
+                CardBuilder::new("Guilde des espions", COLOR_PURPLE, PointsPerNeighbourCard(COLOR_RED, 1)).with_cost_clay(3).with_cost_glass(1),
+                CardBuilder::new("Guilde des magistrats", COLOR_PURPLE, PointsPerNeighbourCard(COLOR_BLUE, 1)).with_cost_wood(3).with_cost_stone(1).with_cost_loom(1),
+                CardBuilder::new("Guilde des stratèges", COLOR_PURPLE, PointsPerNeighbourDefeat(1)).with_cost_ore(2).with_cost_stone(1).with_cost_loom(1),
+                CardBuilder::new("Guilde des armateurs", COLOR_PURPLE, PointsPerOwnCard(vec![COLOR_BROWN, COLOR_GRAY, COLOR_PURPLE], 1)).with_cost_wood(3).with_cost_glass(1).with_cost_papyrus(1),
+                CardBuilder::new("Guilde des scientifiques", COLOR_PURPLE, AnyScience).with_cost_wood(2).with_cost_ore(2).with_cost_papyrus(1),
+                CardBuilder::new("Guilde des bâtisseurs", COLOR_PURPLE, PointsPerWonderStageWithNeighbours(1)).with_cost_stone(2).with_cost_clay(2).with_cost_glass(1),
+                CardBuilder::new("Guilde des décorateurs", COLOR_PURPLE, PointsForCompleteWonder(7)).with_cost_ore(2).with_cost_stone(1).with_cost_loom(1),
+            ].into_iter().map(|cb| cb.build()).collect(),
         }
     }
 }
 
+/// A card that is part of the deck when there are at least `min_players` players.
+#[derive(Debug, Clone)]
+struct CardDefinition {
+    min_players: usize,
+    card: Card,
+}
+
+/// Build the definitions of the cards that are added to the deck starting from `min_players` players.
+fn for_players(min_players: usize, cards: Vec<CardBuilder>) -> Vec<CardDefinition> {
+    cards.into_iter().map(|cb| CardDefinition { min_players, card: cb.build() }).collect()
+}
+
 struct CardBuilder {
     card: Card,
 }
@@ -124,15 +414,58 @@ impl CardBuilder {
         self.card.gold_cost = gold_cost;
         self
     }
-    pub fn with_chaining_source(mut self, source: String) -> Self {
-        self.card.chaining_sources.push(source);
+    pub fn with_chaining_source<S: ToString>(mut self, source: S) -> Self {
+        self.card.chaining_sources.push(source.to_string());
         self
     }
-    pub fn with_chaining_target(mut self, target: String) -> Self {
-        self.card.chaining_targets.push(target);
+    pub fn with_chaining_target<S: ToString>(mut self, target: S) -> Self {
+        self.card.chaining_targets.push(target.to_string());
         self
     }
     pub fn build(self) -> Card {
         self.card
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seven_cards_per_player() {
+        let registry = CardRegistry::new();
+        for age in 0..3 {
+            for player_count in 3..=7 {
+                let cards = registry.generate_cards(age, player_count, &mut thread_rng());
+                assert_eq!(cards.len(), player_count * 7, "age {}, {} players", age + 1, player_count);
+            }
+        }
+    }
+
+    #[test]
+    fn copies_depend_on_player_count() {
+        let registry = CardRegistry::new();
+        let count = |player_count, name| {
+            registry.generate_cards(0, player_count, &mut thread_rng()).iter().filter(|card| card.name == name).count()
+        };
+        assert_eq!(count(3, "Chantier"), 1);
+        assert_eq!(count(4, "Chantier"), 2);
+        assert_eq!(count(4, "Taverne"), 1);
+        assert_eq!(count(7, "Taverne"), 3);
+    }
+
+    #[test]
+    fn guilds_are_distinct() {
+        let registry = CardRegistry::new();
+        for player_count in 3..=7 {
+            let mut guilds: Vec<_> = registry.generate_cards(2, player_count, &mut thread_rng()).into_iter()
+                .filter(|card| card.color == COLOR_PURPLE)
+                .map(|card| card.name)
+                .collect();
+            assert_eq!(guilds.len(), player_count + 2);
+            guilds.sort();
+            guilds.dedup();
+            assert_eq!(guilds.len(), player_count + 2);
+        }
+    }
+}
@@ -1,123 +1,446 @@
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
 use crate::cards::CardRegistry;
+use crate::journal::{Journal, JournalEntry};
+use crate::resources::{cheapest_allocation, payment_options};
+use crate::scoring::{compute_scores, Score};
+use crate::wonders::WonderRegistry;
+
+/// A step of the game that needs randomness.
+#[derive(Debug, Clone, Copy)]
+pub enum RandomStep {
+    /// Assign the seats to the players.
+    Seats,
+    /// Give a wonder to every player.
+    Wonders,
+    /// Deal the cards of an age.
+    Deal(usize),
+}
+
+/// Create the random number generator of one step of a game, so that the seed alone determines the whole game.
+pub fn seeded_rng(seed: u64, step: RandomStep) -> StdRng {
+    let step = match step {
+        RandomStep::Seats => 0,
+        RandomStep::Wonders => 1,
+        RandomStep::Deal(age) => 2 + age as u64,
+    };
+    let mut rng_seed = <StdRng as SeedableRng>::Seed::default();
+    rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    rng_seed[8..16].copy_from_slice(&step.to_le_bytes());
+    StdRng::from_seed(rng_seed)
+}
+
+/// The (resolved turns, phase) of some legal actions, with the legal actions of the players.
+type LegalActionsCache = ((usize, Phase), Vec<Option<Vec<LegalAction>>>);
 
 /// The state of a single game.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
-    #[serde(skip)]
+    /// The card definitions are not part of the state.
+    #[serde(skip, default = "CardRegistry::new")]
     card_registry: CardRegistry,
-    #[serde(skip)]
-    pub plays: Vec<Play>,
+    plays: Vec<Play>,
     player_count: usize,
-    pub players: Vec<PlayerData>,
+    players: Vec<PlayerData>,
     age: usize,
+    /// The seed that determines every random choice of the game.
+    seed: u64,
+    /// The cards that were discarded since the beginning of the game.
+    discard_pile: Vec<Card>,
+    /// The decision the game is waiting for.
+    phase: Phase,
+    /// The players that will build a card from the discard pile at the end of the turn.
+    pending_discard_builds: Vec<usize>,
+    /// The final scores, once the game is over.
+    scores: Option<Vec<Score>>,
+    /// The accepted actions and the turn resolutions since the beginning of the game.
+    journal: Journal,
+    /// The legal actions of the players that haven't picked a card, for the (resolved turns, phase) they were computed in.
+    #[serde(skip)]
+    legal_actions_cache: RefCell<Option<LegalActionsCache>>,
 }
 
 impl Game {
-    pub fn new(player_count: usize) -> Game {
-        let mut rng = thread_rng();
+    pub fn new(player_count: usize, seed: u64) -> Game {
+        let mut rng = seeded_rng(seed, RandomStep::Wonders);
 
-        let card_registry = CardRegistry::new();
-        let mut cards = card_registry.generate_cards(0, player_count);
-        cards.shuffle(&mut rng);
+        let wonders = WonderRegistry::new().generate_wonders(player_count, &mut rng);
 
-        let players = (0..player_count).map(|i| {
-            PlayerData {
-                hand_cards: cards[(i*7)..((i+1)*7)].into(),
-                board_cards: vec![],
-                resource_productions: Default::default(),
-                resource_costs: [[2; 7], [0; 7], [2; 7]],
-                gold: 3,
-            }
-        }).collect();
+        let players = wonders.into_iter().map(PlayerData::new).collect();
 
-        Game {
-            card_registry,
+        let mut game = Game {
+            card_registry: CardRegistry::new(),
             player_count,
             plays: vec![Play::NoAction; player_count],
             players,
             age: 0,
+            seed,
+            discard_pile: vec![],
+            phase: Phase::PlayingCards,
+            pending_discard_builds: vec![],
+            scores: None,
+            journal: Journal::new(player_count, seed),
+            legal_actions_cache: RefCell::new(None),
+        };
+        for i in 0..player_count {
+            let starting_resource = game.players[i].wonder.starting_resource;
+            game.add_production(i, starting_resource, false);
+        }
+        game.deal_cards();
+        game
+    }
+
+    /// Deal the cards of the current age to the players.
+    fn deal_cards(&mut self) {
+        let mut rng = seeded_rng(self.seed, RandomStep::Deal(self.age));
+
+        let mut cards = self.card_registry.generate_cards(self.age, self.player_count, &mut rng);
+        cards.shuffle(&mut rng);
+        for (player, hand) in self.players.iter_mut().zip(cards.chunks(7)) {
+            player.hand_cards = hand.to_vec();
+        }
+    }
+
+    /// Make a production available to a player, and to the adjacent players if it is tradable.
+    fn add_production(&mut self, player: usize, production: ResourceArray, tradable: bool) {
+        let left = (player + self.player_count - 1) % self.player_count;
+        let right = (player + 1) % self.player_count;
+        for production in split_production(production) {
+            if tradable {
+                self.players[left].resource_productions[2].push(production);
+                self.players[right].resource_productions[0].push(production);
+            }
+            self.players[player].resource_productions[1].push(production);
+        }
+    }
+
+    /// Apply the immediate effect of a card or wonder stage built by a player.
+    fn apply_effect(&mut self, player: usize, effect: &CardEffect) {
+        use CardEffect::*;
+
+        match *effect {
+            Resources(ra) => {
+                self.add_production(player, ra, true);
+            },
+            PrivateResources(ra) => {
+                self.add_production(player, ra, false);
+            },
+            RawMaterialsCost(neighbour) => {
+                for resource in 0..4 {
+                    self.players[player].resource_costs[neighbour][resource] = 1;
+                }
+            },
+            ManufacturedProductsCost => {
+                for neighbour in &[0, 2] {
+                    for resource in 4..7 {
+                        self.players[player].resource_costs[*neighbour][resource] = 1;
+                    }
+                }
+            },
+            Gold(gold) => {
+                self.players[player].gold += gold;
+            },
+            GoldPerCard(color, gold) => {
+                let left = (player + self.player_count - 1) % self.player_count;
+                let right = (player + 1) % self.player_count;
+                let card_count: u32 = [left, player, right].iter().map(|&i| self.players[i].count_cards(color)).sum();
+                self.players[player].gold += card_count * gold;
+            },
+            GoldAndPointsPerCard(color, gold, _) => {
+                self.players[player].gold += self.players[player].count_cards(color) * gold;
+            },
+            GoldAndPointsPerWonderStage(gold, _) => {
+                self.players[player].gold += self.players[player].wonder_stages_built as u32 * gold;
+            },
+            BuildFromDiscard => {
+                self.pending_discard_builds.push(player);
+            },
+            Points(_) | Shields(_) | Science(_) => (),
+            PointsPerNeighbourCard(_, _) | PointsPerNeighbourDefeat(_) | PointsPerWonderStageWithNeighbours(_) | PointsPerOwnCard(_, _) | PointsForCompleteWonder(_) | AnyScience => (),
+            FreeBuildOncePerAge | PlaySeventhCard | CopyGuild => (),
         }
     }
 
-    pub fn perform_action(&mut self, player: usize, action: PlayerAction) {
-        assert!(player < self.player_count);
+    pub fn perform_action(&mut self, player: usize, action: PlayerAction) -> Result<(), ActionError> {
+        if player >= self.player_count {
+            return Err(ActionError::InvalidPlayer);
+        }
+        if self.scores.is_some() {
+            return Err(ActionError::WrongPhase);
+        }
         let adjacent_players = {
             let previous_player = (player + self.player_count - 1) % self.player_count;
             let next_player = (player + 1) % self.player_count;
             [previous_player, player, next_player]
         };
 
+        // Outside of the card playing phases, only the corresponding wonder power can be used
+        let can_play_cards = match self.phase {
+            Phase::PlayingCards => true,
+            Phase::PlayingSeventhCard(p) => p == player,
+            Phase::BuildingFromDiscard(_) | Phase::CopyingGuild(_) => false,
+        };
+        let is_card_action = !matches!(action, PlayerAction::BuildFromDiscard(_) | PlayerAction::CopyGuild(_, _));
+        if is_card_action && !can_play_cards {
+            return Err(ActionError::WrongPhase);
+        }
+
+        let journal_entry = JournalEntry::Action { player, action: action.clone() };
         let play = &mut self.plays[player];
         let data = &self.players[player];
         match action {
             PlayerAction::PickCard(card) => {
-                // Check card index
-                println!("Picking card {} for player {}!", card, player);
-                if let Play::NoAction = play {
-                    println!("Picking card {} for player {}!", card, player);
-                    if card < data.hand_cards.len() {
-                        println!("Picking card {} for player {}!", card, player);
-                        let no_resource_allocation = {
-                            let mut nra: ResourceAllocation = Default::default();
-                            for i in 0..3 {
-                                nra[i] = vec![0; data.resource_productions[i].len()];
-                            }
-                            nra
-                        };
-                        let verdict = data.get_card_verdict(card, &no_resource_allocation);
-                        dbg!(card);
-                        *play = Play::ChoosingResources {
-                            card_index: card,
-                            resource_allocation: no_resource_allocation,
-                            verdict,
-                        };
-                    }
+                data.check_card_pick(play, card)?;
+                if data.has_built(&data.hand_cards[card].name) {
+                    return Err(ActionError::AlreadyBuilt);
+                }
+                let no_resource_allocation = data.empty_resource_allocation();
+                let verdict = data.get_card_verdict(card, PlayTarget::Board, &no_resource_allocation);
+                *play = Play::ChoosingResources {
+                    card_index: card,
+                    target: PlayTarget::Board,
+                    resource_allocation: no_resource_allocation,
+                    verdict,
+                };
+            },
+            PlayerAction::BuildWonderStage(card) => {
+                data.check_card_pick(play, card)?;
+                if data.wonder_stages_built == data.wonder.stages.len() {
+                    return Err(ActionError::WonderComplete);
+                }
+                let no_resource_allocation = data.empty_resource_allocation();
+                let verdict = data.get_card_verdict(card, PlayTarget::WonderStage, &no_resource_allocation);
+                *play = Play::ChoosingResources {
+                    card_index: card,
+                    target: PlayTarget::WonderStage,
+                    resource_allocation: no_resource_allocation,
+                    verdict,
+                };
+            },
+            PlayerAction::DiscardCard(card) => {
+                data.check_card_pick(play, card)?;
+                *play = Play::ChoseResources {
+                    card_index: card,
+                    target: PlayTarget::Discard,
+                    resource_allocation: data.empty_resource_allocation(),
+                };
+            },
+            PlayerAction::BuildForFree(card) => {
+                data.check_card_pick(play, card)?;
+                if !data.has_power(&CardEffect::FreeBuildOncePerAge) || data.free_build_used {
+                    return Err(ActionError::PowerUnavailable);
+                }
+                if data.has_built(&data.hand_cards[card].name) {
+                    return Err(ActionError::AlreadyBuilt);
+                }
+                *play = Play::ChoseResources {
+                    card_index: card,
+                    target: PlayTarget::BoardForFree,
+                    resource_allocation: data.empty_resource_allocation(),
+                };
+            },
+            PlayerAction::BuildFromDiscard(card) => {
+                if self.phase != Phase::BuildingFromDiscard(player) {
+                    return Err(ActionError::WrongPhase);
+                }
+                if card >= self.discard_pile.len() {
+                    return Err(ActionError::InvalidCardIndex);
                 }
+                if data.has_built(&self.discard_pile[card].name) {
+                    return Err(ActionError::AlreadyBuilt);
+                }
+                let card = self.discard_pile.remove(card);
+                let effect = card.effect.clone();
+                self.players[player].board_cards.push(card);
+                self.apply_effect(player, &effect);
+            },
+            PlayerAction::CopyGuild(neighbour, card) => {
+                if self.phase != Phase::CopyingGuild(player) {
+                    return Err(ActionError::WrongPhase);
+                }
+                if neighbour != LEFT_PLAYER && neighbour != RIGHT_PLAYER {
+                    return Err(ActionError::InvalidPlayer);
+                }
+                let guild = self.players[adjacent_players[neighbour]].board_cards.get(card).ok_or(ActionError::InvalidCardIndex)?;
+                if guild.color != COLOR_PURPLE {
+                    return Err(ActionError::NotAGuild);
+                }
+                self.players[player].copied_guild = Some(guild.clone());
             },
             PlayerAction::CancelCard => {
+                if let Play::NoAction = play {
+                    return Err(ActionError::UnexpectedAction);
+                }
                 *play = Play::NoAction;
             },
-            PlayerAction::GetResource(player, production, resource) => {
-                if player < 3 && production < data.resource_productions[player].len() && resource < 8 {
-                    if let Play::ChoosingResources { card_index, resource_allocation, verdict } = play {
-                        resource_allocation[player][production] = resource;
-                        *verdict = data.get_card_verdict(*card_index, &resource_allocation);
+            PlayerAction::GetResource(position, production, resource) => {
+                if let Play::ChoosingResources { card_index, target, resource_allocation, verdict } = play {
+                    let produced = data.resource_productions.get(position).and_then(|productions| productions.get(production));
+                    match produced {
+                        Some(produced) if resource == 0 || (resource <= 7 && produced[resource - 1] > 0) => (),
+                        _ => return Err(ActionError::InvalidResource),
                     }
+                    resource_allocation[position][production] = resource;
+                    *verdict = data.get_card_verdict(*card_index, *target, &resource_allocation);
+                } else {
+                    return Err(ActionError::UnexpectedAction);
+                }
+            },
+            PlayerAction::ChooseCheapestResources => {
+                if let Play::ChoosingResources { card_index, target, resource_allocation, verdict } = play {
+                    *resource_allocation = data.cheapest_resource_allocation(*card_index, *target).ok_or(ActionError::MissingResources)?;
+                    *verdict = data.get_card_verdict(*card_index, *target, &resource_allocation);
+                } else {
+                    return Err(ActionError::UnexpectedAction);
                 }
             },
             PlayerAction::Validate => {
-                if let Play::ChoosingResources { card_index, resource_allocation, verdict } = play {
-                    if verdict.is_valid() {
-                        *play = Play::ChoseResources {
-                            card_index: *card_index,
-                            resource_allocation: resource_allocation.clone(),
-                        };
+                if let Play::ChoosingResources { card_index, target, resource_allocation, verdict } = play {
+                    if !verdict.has_right_resources() {
+                        return Err(ActionError::InvalidResourceAllocation);
                     }
+                    if verdict.missing_gold > 0 {
+                        return Err(ActionError::NotEnoughGold);
+                    }
+                    *play = Play::ChoseResources {
+                        card_index: *card_index,
+                        target: *target,
+                        resource_allocation: resource_allocation.clone(),
+                    };
+                } else {
+                    return Err(ActionError::UnexpectedAction);
                 }
             },
             PlayerAction::Unvalidate => {
-                if let Play::ChoseResources { card_index, resource_allocation } = play {
-                    let verdict = data.get_card_verdict(*card_index, &resource_allocation);
+                if let Play::ChoseResources { target: PlayTarget::Discard, .. } | Play::ChoseResources { target: PlayTarget::BoardForFree, .. } = play {
+                    *play = Play::NoAction;
+                } else if let Play::ChoseResources { card_index, target, resource_allocation } = play {
+                    let verdict = data.get_card_verdict(*card_index, *target, &resource_allocation);
                     *play = Play::ChoosingResources {
                         card_index: *card_index,
+                        target: *target,
                         resource_allocation: resource_allocation.clone(),
                         verdict,
                     };
+                } else {
+                    return Err(ActionError::UnexpectedAction);
+                }
+            },
+        }
+
+        // Record the action before resolving its consequences, so that it comes before the turn resolution
+        self.journal.entries.push(journal_entry);
+        match self.phase {
+            Phase::PlayingCards | Phase::PlayingSeventhCard(_) => self.maybe_play_cards(),
+            Phase::BuildingFromDiscard(_) => self.end_turn(),
+            Phase::CopyingGuild(_) => self.end_game(),
+        }
+        Ok(())
+    }
+
+    /// List every decision the player can make right now, starting from a card that isn't picked yet,
+    /// with all the distinct minimal ways to pay the neighbours.
+    pub fn legal_actions(&self, player: usize) -> Vec<LegalAction> {
+        let data = &self.players[player];
+        let mut actions = vec![];
+        match self.phase {
+            Phase::PlayingCards | Phase::PlayingSeventhCard(_) => {
+                let can_play_cards = match self.phase {
+                    Phase::PlayingSeventhCard(p) => p == player,
+                    _ => true,
+                };
+                let has_picked_card = match self.plays[player] {
+                    Play::NoAction => false,
+                    Play::ChoosingResources {..} | Play::ChoseResources {..} => true,
+                };
+                if !can_play_cards || has_picked_card {
+                    return actions;
+                }
+                for card_index in 0..data.hand_cards.len() {
+                    let mut targets = vec![PlayTarget::Discard];
+                    if !data.has_built(&data.hand_cards[card_index].name) {
+                        targets.push(PlayTarget::Board);
+                        if data.has_power(&CardEffect::FreeBuildOncePerAge) && !data.free_build_used {
+                            targets.push(PlayTarget::BoardForFree);
+                        }
+                    }
+                    if data.wonder_stages_built < data.wonder.stages.len() {
+                        targets.push(PlayTarget::WonderStage);
+                    }
+                    for target in targets {
+                        for resource_allocation in data.payment_options(card_index, target) {
+                            actions.push(LegalAction::Play { card_index, target, resource_allocation });
+                        }
+                    }
+                }
+            },
+            Phase::BuildingFromDiscard(p) if p == player => {
+                for (card_index, card) in self.discard_pile.iter().enumerate() {
+                    if !data.has_built(&card.name) {
+                        actions.push(LegalAction::BuildFromDiscard(card_index));
+                    }
+                }
+            },
+            Phase::CopyingGuild(p) if p == player => {
+                for &side in &[LEFT_PLAYER, RIGHT_PLAYER] {
+                    let neighbour = (player + self.player_count + side - 1) % self.player_count;
+                    for (card_index, card) in self.players[neighbour].board_cards.iter().enumerate() {
+                        if card.color == COLOR_PURPLE {
+                            actions.push(LegalAction::CopyGuild(side, card_index));
+                        }
+                    }
                 }
             },
+            Phase::BuildingFromDiscard(_) | Phase::CopyingGuild(_) => (),
         }
+        actions
+    }
 
-        self.maybe_play_cards();
+    /// Check whether the game is waiting for a decision of the player.
+    pub fn is_waiting_for(&self, player: usize) -> bool {
+        let has_validated = match self.plays[player] {
+            Play::ChoseResources {..} => true,
+            Play::NoAction | Play::ChoosingResources {..} => false,
+        };
+        self.scores.is_none() && match self.phase {
+            Phase::PlayingCards => !has_validated,
+            Phase::PlayingSeventhCard(p) => p == player && !has_validated,
+            Phase::BuildingFromDiscard(p) | Phase::CopyingGuild(p) => p == player,
+        }
+    }
+
+    /// Decide for a player who ran out of time: discard a card, or take the first option of a wonder power.
+    pub fn play_automatically(&mut self, player: usize) -> Result<(), ActionError> {
+        if !self.is_waiting_for(player) {
+            return Ok(());
+        }
+        if let Play::ChoosingResources {..} = self.plays[player] {
+            self.perform_action(player, PlayerAction::CancelCard)?;
+        }
+        let legal_actions = self.legal_actions(player);
+        let discard = legal_actions.iter().find(|legal_action| matches!(legal_action, LegalAction::Play { target: PlayTarget::Discard, .. }));
+        if let Some(legal_action) = discard.or_else(|| legal_actions.first()) {
+            for action in legal_action.player_actions() {
+                self.perform_action(player, action)?;
+            }
+        }
+        Ok(())
     }
 
     fn maybe_play_cards(&mut self) {
-        let ready_players = self.plays.iter().filter(|p| match p {
+        let is_ready = |p: &Play| match p {
             Play::ChoseResources {..} => true,
             Play::NoAction | Play::ChoosingResources {..} => false,
-        }).count();
-        if ready_players == self.player_count {
+        };
+        let ready = match self.phase {
+            Phase::PlayingCards => self.plays.iter().all(is_ready),
+            Phase::PlayingSeventhCard(player) => is_ready(&self.plays[player]),
+            Phase::BuildingFromDiscard(_) | Phase::CopyingGuild(_) => false,
+        };
+        if ready {
             self.play_cards();
         }
     }
@@ -126,15 +449,38 @@ impl Game {
         // Place cards on the board
         let player_count = self.player_count;
         let adjancent_players = |i| [(i + player_count - 1) % player_count, (i + 1) % player_count];
+        let mut effects = vec![];
         for i in 0..player_count {
-            if let Play::ChoseResources { card_index, resource_allocation } = &self.plays[i] {
-                let (gold_cost, gold_gain) = self.players[i].get_gold_cost(*card_index, &resource_allocation);
-                assert!(gold_gain[1] == 0);
+            if let Play::ChoseResources { card_index, target, resource_allocation } = &self.plays[i] {
+                let (gold_cost, gold_gain) = self.players[i].get_gold_cost(*card_index, *target, &resource_allocation);
                 // Remove card from hand
                 let card = self.players[i].hand_cards.remove(*card_index);
-                // Add card to board
-                self.players[i].board_cards.push(card);
-                // Transfer gold
+                match target {
+                    PlayTarget::Board => {
+                        // Add card to board
+                        effects.push((i, card.effect.clone()));
+                        self.players[i].board_cards.push(card);
+                    },
+                    PlayTarget::BoardForFree => {
+                        // Use the free build of this age
+                        effects.push((i, card.effect.clone()));
+                        self.players[i].board_cards.push(card);
+                        self.players[i].free_build_used = true;
+                    },
+                    PlayTarget::WonderStage => {
+                        // Build the next stage, the card stays hidden under the wonder
+                        let stage = &self.players[i].wonder.stages[self.players[i].wonder_stages_built];
+                        effects.extend(stage.effects.iter().map(|effect| (i, effect.clone())));
+                        self.players[i].wonder_stages_built += 1;
+                    },
+                    PlayTarget::Discard => {
+                        // Sell the card
+                        self.discard_pile.push(card);
+                        self.players[i].gold += 3;
+                    },
+                }
+                // Transfer gold, the play was affordable when it was validated and gold is only spent here
+                debug_assert!(self.players[i].gold >= gold_cost, "Player {} can't afford a validated play", i);
                 self.players[i].gold -= gold_cost;
                 let [left, right] = adjancent_players(i);
                 self.players[left].gold += gold_gain[0];
@@ -143,60 +489,332 @@ impl Game {
             // Reset actions
             self.plays[i] = Play::NoAction;
         }
-        // Apply card effects
-        for i in 0..self.player_count {
-            use CardEffect::*;
-
-            let [left, right] = adjancent_players(i);
-            let card = self.players[i].board_cards.last().clone().expect("No card on board");
-            match card.effect {
-                Resources(ra) => {
-                    self.players[left].resource_productions[2].push(ra.clone());
-                    self.players[i].resource_productions[1].push(ra.clone());
-                    self.players[right].resource_productions[0].push(ra.clone());
-                },
-                RawMaterialsCost(player) => {
-                    for resource in 0..4 {
-                        self.players[i].resource_costs[player][resource] = 1;
-                    }
-                },
-                ManufacturedProductsCost => {
-                    for player in &[0, 2] {
-                        for resource in 4..7 {
-                            self.players[i].resource_costs[*player][resource] = 1;
-                        }
-                    }
-                },
-                Points(_) | Shields(_) | Science(_) => (),
+        // Apply card and wonder stage effects
+        for (i, effect) in effects {
+            self.apply_effect(i, &effect);
+        }
+        self.end_turn();
+    }
+
+    /// Finish the current turn, stopping whenever a player has to make a decision because of a wonder power.
+    /// It is called again once the decision is made, and the turn is recorded as resolved when nothing is left to decide.
+    fn end_turn(&mut self) {
+        let age = self.age;
+        self.phase = Phase::PlayingCards;
+        let last_turn = self.players.iter().all(|player| player.hand_cards.len() <= 1);
+        if last_turn {
+            // Babylon can play the seventh card instead of discarding it
+            let seventh_card_player = (0..self.player_count).find(|&i| {
+                self.players[i].hand_cards.len() == 1 && self.players[i].has_power(&CardEffect::PlaySeventhCard)
+            });
+            if let Some(player) = seventh_card_player {
+                self.phase = Phase::PlayingSeventhCard(player);
+                return;
+            }
+            for player in self.players.iter_mut() {
+                self.discard_pile.append(&mut player.hand_cards);
             }
         }
-        // Rotate cards
+        // Halicarnassus builds a card from the discard pile, if there is one that it hasn't built yet
+        while let Some(player) = self.pending_discard_builds.pop() {
+            if self.discard_pile.iter().any(|card| !self.players[player].has_built(&card.name)) {
+                self.phase = Phase::BuildingFromDiscard(player);
+                return;
+            }
+        }
+        if last_turn {
+            self.end_age();
+        } else {
+            self.rotate_cards();
+        }
+        let state_hash = self.state_hash();
+        self.journal.entries.push(JournalEntry::TurnResolved { age, state_hash });
+    }
+
+    /// Pass the hands to the left in the first and third ages, and to the right in the second age.
+    fn rotate_cards(&mut self) {
         let mut remaining_cards: Vec<_> = self.players.iter().map(|player| player.hand_cards.clone()).collect();
-        // TODO: check rotation direction
-        remaining_cards.rotate_left(1);
-        for i in 0..player_count {
+        if self.age == 1 {
+            remaining_cards.rotate_right(1);
+        } else {
+            remaining_cards.rotate_left(1);
+        }
+        for i in 0..self.player_count {
             self.players[i].hand_cards = remaining_cards[i].clone();
         }
-        // TODO: check age end
-        // TODO: check game end
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.player_count
+    }
+
+    /// The number of turns that were resolved since the beginning of the game.
+    pub fn resolved_turns(&self) -> usize {
+        self.journal.entries.iter().filter(|entry| match entry {
+            JournalEntry::TurnResolved {..} => true,
+            JournalEntry::Action {..} => false,
+        }).count()
+    }
+
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    pub fn get_scores(&self) -> Option<&Vec<Score>> {
+        self.scores.as_ref()
+    }
+
+    /// Build what a player is allowed to know about the game.
+    pub fn player_view(&self, player: usize) -> PlayerView {
+        let players = self.players.iter().zip(&self.plays).map(|(data, play)| {
+            PublicPlayerData {
+                board_cards: data.board_cards.clone(),
+                resource_productions: data.resource_productions.clone(),
+                resource_costs: data.resource_costs,
+                gold: data.gold,
+                wonder: data.wonder.clone(),
+                wonder_stages_built: data.wonder_stages_built,
+                military_tokens: data.military_tokens.clone(),
+                copied_guild: data.copied_guild.clone(),
+                hand_size: data.hand_cards.len(),
+                ready: match play {
+                    Play::ChoseResources {..} => true,
+                    Play::NoAction | Play::ChoosingResources {..} => false,
+                },
+            }
+        }).collect();
+        PlayerView {
+            player,
+            age: self.age,
+            phase: self.phase,
+            players,
+            hand: self.players[player].hand_cards.clone(),
+            play: self.plays[player].clone(),
+            discard_pile: if self.phase == Phase::BuildingFromDiscard(player) {
+                Some(self.discard_pile.clone())
+            } else {
+                None
+            },
+            legal_actions: self.cached_legal_actions(player),
+            scores: self.scores.clone(),
+        }
+    }
+
+    /// The legal actions of a player, computed at most once per turn and phase since the payment search is expensive.
+    /// Hands, boards and gold only change when a turn or a phase ends, and a player who picked a card has nothing to list.
+    fn cached_legal_actions(&self, player: usize) -> Vec<LegalAction> {
+        if let Play::ChoosingResources {..} | Play::ChoseResources {..} = self.plays[player] {
+            return self.legal_actions(player);
+        }
+        let turn = (self.resolved_turns(), self.phase);
+        let mut cache = self.legal_actions_cache.borrow_mut();
+        match &*cache {
+            Some((cached_turn, _)) if *cached_turn == turn => (),
+            _ => *cache = Some((turn, vec![None; self.player_count])),
+        }
+        let (_, legal_actions) = cache.as_mut().expect("The cache was just filled");
+        legal_actions[player].get_or_insert_with(|| self.legal_actions(player)).clone()
+    }
+
+    /// Take a snapshot of the complete state of the game, hidden information included.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            version: SNAPSHOT_VERSION,
+            game: self.clone(),
+        }
+    }
+
+    /// A fingerprint of the complete state of the game, journal excepted.
+    /// It depends on the serialized state, so it can be compared across runs of the same build of the server,
+    /// but any change to the state types or the card definitions changes it.
+    pub fn state_hash(&self) -> u64 {
+        // 64-bit FNV-1a
+        self.serialized_state().iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+    }
+
+    /// The serialized state of the game, journal excepted, to compare or fingerprint states.
+    pub fn serialized_state(&self) -> Vec<u8> {
+        let mut game = self.clone();
+        game.journal = Journal::default();
+        // Every field is plain data with string map keys, so serialization can't fail
+        serde_json::to_vec(&game).expect("Game states can always be serialized")
+    }
+
+    /// Resume a game from a snapshot.
+    pub fn from_snapshot(snapshot: GameSnapshot) -> Result<Game, SnapshotError> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+        }
+        let game = snapshot.game;
+        if game.players.len() != game.player_count || game.plays.len() != game.player_count {
+            return Err(SnapshotError::WrongPlayerCount);
+        }
+        Ok(game)
+    }
+
+    /// Resolve the military conflicts and start the next age, or end the game after the third age.
+    fn end_age(&mut self) {
+        self.resolve_military_conflicts();
+        if self.age < 2 {
+            self.age += 1;
+            for player in self.players.iter_mut() {
+                player.free_build_used = false;
+            }
+            self.deal_cards();
+        } else {
+            self.end_game();
+        }
+    }
+
+    /// Let Olympia copy a guild of an adjacent player, then compute the scores.
+    fn end_game(&mut self) {
+        let copying_player = (0..self.player_count).find(|&i| {
+            let neighbours = [(i + self.player_count - 1) % self.player_count, (i + 1) % self.player_count];
+            let neighbours_have_guild = neighbours.iter().any(|&neighbour| self.players[neighbour].count_cards(COLOR_PURPLE) > 0);
+            self.players[i].has_power(&CardEffect::CopyGuild) && self.players[i].copied_guild.is_none() && neighbours_have_guild
+        });
+        if let Some(player) = copying_player {
+            self.phase = Phase::CopyingGuild(player);
+        } else {
+            self.phase = Phase::PlayingCards;
+            self.scores = Some(compute_scores(&self.players));
+        }
+    }
+
+    /// Compare the shields of every player with those of the adjacent players,
+    /// giving a victory token worth 1, 3 or 5 points depending on the age, or a defeat token worth -1 point.
+    fn resolve_military_conflicts(&mut self) {
+        let victory_points = [1, 3, 5][self.age];
+        let shields: Vec<_> = self.players.iter().map(|player| player.get_shields()).collect();
+        for i in 0..self.player_count {
+            let neighbours = [(i + self.player_count - 1) % self.player_count, (i + 1) % self.player_count];
+            for &neighbour in &neighbours {
+                if shields[i] > shields[neighbour] {
+                    self.players[i].military_tokens.push(victory_points);
+                } else if shields[i] < shields[neighbour] {
+                    self.players[i].military_tokens.push(-1);
+                }
+            }
+        }
     }
 }
 
 /// The state of one player's board and hand.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerData {
-    #[serde(skip)]
     pub hand_cards: Vec<Card>,
-    board_cards: Vec<Card>,
+    pub board_cards: Vec<Card>,
     resource_productions: [Vec<ResourceArray>; 3],
     resource_costs: [ResourceArray; 3],
-    gold: u32,
+    pub gold: u32,
+    pub wonder: Wonder,
+    pub wonder_stages_built: usize,
+    /// The military victory (positive) and defeat (negative) tokens.
+    pub military_tokens: Vec<i32>,
+    /// Whether the free build of the current age was used.
+    free_build_used: bool,
+    /// The guild of an adjacent player that was copied at the end of the game.
+    pub copied_guild: Option<Card>,
 }
 
 impl PlayerData {
-    pub fn get_card_verdict(&self, card: usize, resource_allocation: &ResourceAllocation) -> ResourceAllocationVerdict {
+    /// A player at the beginning of the game, without the starting resource of the wonder.
+    pub fn new(wonder: Wonder) -> PlayerData {
+        PlayerData {
+            hand_cards: vec![],
+            board_cards: vec![],
+            resource_productions: Default::default(),
+            resource_costs: [[2; 7], [0; 7], [2; 7]],
+            gold: 3,
+            wonder,
+            wonder_stages_built: 0,
+            military_tokens: vec![],
+            free_build_used: false,
+            copied_guild: None,
+        }
+    }
+
+    /// The effects of the cards on the board, of the copied guild and of the wonder stages that were built.
+    pub fn built_effects(&self) -> impl Iterator<Item = &CardEffect> {
+        let built_stages = &self.wonder.stages[..self.wonder_stages_built];
+        self.board_cards.iter()
+            .chain(self.copied_guild.iter())
+            .map(|card| &card.effect)
+            .chain(built_stages.iter().flat_map(|stage| stage.effects.iter()))
+    }
+
+    /// Check whether a wonder power was unlocked by building a wonder stage.
+    pub fn has_power(&self, power: &CardEffect) -> bool {
+        self.wonder.stages[..self.wonder_stages_built].iter().any(|stage| stage.effects.contains(power))
+    }
+
+    /// Count the cards of some color on the board.
+    pub fn count_cards(&self, color: usize) -> u32 {
+        self.board_cards.iter().filter(|card| card.color == color).count() as u32
+    }
+
+    pub fn get_shields(&self) -> u32 {
+        self.built_effects().map(|effect| match effect {
+            CardEffect::Shields(shields) => *shields,
+            _ => 0,
+        }).sum()
+    }
+
+    /// Check that the player can pick the i-th card of the hand.
+    fn check_card_pick(&self, play: &Play, card: usize) -> Result<(), ActionError> {
+        if let Play::NoAction = play {
+            if card < self.hand_cards.len() {
+                Ok(())
+            } else {
+                Err(ActionError::InvalidCardIndex)
+            }
+        } else {
+            Err(ActionError::UnexpectedAction)
+        }
+    }
+
+    /// An allocation that doesn't use any production.
+    pub fn empty_resource_allocation(&self) -> ResourceAllocation {
+        let mut nra: ResourceAllocation = Default::default();
+        for i in 0..3 {
+            nra[i] = vec![0; self.resource_productions[i].len()];
+        }
+        nra
+    }
+
+    /// Check whether a card with the same name is already on the board.
+    pub fn has_built(&self, card_name: &str) -> bool {
+        self.board_cards.iter().any(|built_card| built_card.name == card_name)
+    }
+
+    /// Check whether the i-th card of the hand can be built for free because its chaining source is on the board.
+    pub fn can_chain(&self, card: usize) -> bool {
+        let sources = &self.hand_cards[card].chaining_sources;
+        self.board_cards.iter().any(|built_card| sources.contains(&built_card.name))
+    }
+
+    /// Get the gold and the resources that must be paid to use the i-th card of the hand for some target.
+    pub fn get_cost(&self, card: usize, target: PlayTarget) -> (u32, ResourceArray) {
+        match target {
+            PlayTarget::Board if self.can_chain(card) => (0, [0; 7]),
+            PlayTarget::Board => (self.hand_cards[card].gold_cost, self.hand_cards[card].resource_cost),
+            PlayTarget::BoardForFree => (0, [0; 7]),
+            PlayTarget::WonderStage => (0, self.wonder.stages[self.wonder_stages_built].resource_cost),
+            PlayTarget::Discard => (0, [0; 7]),
+        }
+    }
+
+    pub fn get_card_verdict(&self, card: usize, target: PlayTarget, resource_allocation: &ResourceAllocation) -> ResourceAllocationVerdict {
+        let (mut gold_cost, card_cost) = self.get_cost(card, target);
         let mut allocated_resources = [0; 7];
-        let mut gold_cost = self.hand_cards[card].gold_cost;
         for player in 0..3 {
             for (production, chosen_resource) in resource_allocation[player].iter().enumerate() {
                 if *chosen_resource > 0 && self.resource_productions[player][production][chosen_resource-1] > 0 {
@@ -207,7 +825,6 @@ impl PlayerData {
             }
         }
 
-        let card_cost = self.hand_cards[card].resource_cost.clone();
         let mut extra_resources = [0; 7];
         let mut missing_resources = [0; 7];
         for i in 0..7 {
@@ -231,11 +848,26 @@ impl PlayerData {
             extra_resources,
             missing_resources,
             missing_gold,
+            free_construction: target == PlayTarget::Board && self.can_chain(card),
         }
     }
 
-    pub fn get_gold_cost(&self, card: usize, resource_allocation: &ResourceAllocation) -> (u32, [u32; 3]) {
-        let mut gold_cost = self.hand_cards[card].gold_cost;
+    /// The cheapest resource allocation to use the i-th card of the hand for some target, if there is one.
+    pub fn cheapest_resource_allocation(&self, card: usize, target: PlayTarget) -> Option<ResourceAllocation> {
+        let (_, resource_cost) = self.get_cost(card, target);
+        cheapest_allocation(&self.resource_productions, &self.resource_costs, resource_cost)
+    }
+
+    /// The distinct minimal resource allocations that the player can afford to use the i-th card of the hand for some target.
+    pub fn payment_options(&self, card: usize, target: PlayTarget) -> Vec<ResourceAllocation> {
+        let (_, resource_cost) = self.get_cost(card, target);
+        payment_options(&self.resource_productions, &self.resource_costs, resource_cost).into_iter()
+            .filter(|allocation| self.get_card_verdict(card, target, allocation).is_valid())
+            .collect()
+    }
+
+    pub fn get_gold_cost(&self, card: usize, target: PlayTarget, resource_allocation: &ResourceAllocation) -> (u32, [u32; 3]) {
+        let (mut gold_cost, _) = self.get_cost(card, target);
         let mut gold_gain = [0; 3];
         for player in 0..3 {
             for (production, chosen_resource) in resource_allocation[player].iter().enumerate() {
@@ -251,7 +883,7 @@ impl PlayerData {
 }
 
 /// A card.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Card {
     pub color: usize,
     pub name: String,
@@ -276,10 +908,13 @@ pub const SCIENCE_TABLET: usize = 0;
 pub const SCIENCE_COMPASS: usize = 1;
 pub const SCIENCE_GEAR: usize = 2;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CardEffect {
-    /// Produce one of the following resource.
+    /// Produce one of the following resource, or several units of it if only one resource is listed.
+    /// Adjacent players can buy it.
     Resources(ResourceArray),
+    /// Produce one of the following resource for the player only: adjacent players can't buy it.
+    PrivateResources(ResourceArray),
     /// Give points.
     Points(u32),
     /// Change the cost of buying raw materials coming from an adjacent player to 1 (left is 0, right is 2).
@@ -289,49 +924,686 @@ pub enum CardEffect {
     /// Give military points.
     Shields(u32),
     Science(usize),
+    /// Give gold for each card of some color on the boards of the player and the adjacent players: (color, gold per card).
+    GoldPerCard(usize, u32),
+    /// Give gold.
+    Gold(u32),
+    /// Give gold now and points at the end of the game for each card of some color on the player's board: (color, gold per card, points per card).
+    GoldAndPointsPerCard(usize, u32, u32),
+    /// Give gold now and points at the end of the game for each wonder stage built by the player: (gold per stage, points per stage).
+    GoldAndPointsPerWonderStage(u32, u32),
+    /// Give points for each card of some color on the boards of the adjacent players: (color, points per card).
+    PointsPerNeighbourCard(usize, u32),
+    /// Give points for each defeat token of the adjacent players.
+    PointsPerNeighbourDefeat(u32),
+    /// Give points for each wonder stage built by the player and the adjacent players.
+    PointsPerWonderStageWithNeighbours(u32),
+    /// Give points for each card of one of the colors on the player's board: (colors, points per card).
+    PointsPerOwnCard(Vec<usize>, u32),
+    /// Give points if all the stages of the player's wonder are built.
+    PointsForCompleteWonder(u32),
+    /// Count as any science symbol.
+    AnyScience,
+    /// Build a card for free once per age.
+    FreeBuildOncePerAge,
+    /// Build a card from the discard pile for free at the end of the turn.
+    BuildFromDiscard,
+    /// Play the seventh card of each age instead of discarding it.
+    PlaySeventhCard,
+    /// Copy a guild built by an adjacent player at the end of the game.
+    CopyGuild,
 }
 
 pub type ResourceArray = [u32; 7];
 
-#[derive(Debug, Clone, Deserialize)]
+/// A wonder board.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wonder {
+    pub name: String,
+    pub side: WonderSide,
+    /// The resource produced by the board from the start of the game, that adjacent players can't buy.
+    pub starting_resource: ResourceArray,
+    pub stages: Vec<WonderStage>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum WonderSide {
+    A,
+    B,
+}
+
+/// A stage of a wonder, built in order by using a card of the hand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WonderStage {
+    pub resource_cost: ResourceArray,
+    pub effects: Vec<CardEffect>,
+}
+
+/// Split a production into the productions that can each be allocated to one resource.
+/// A production of several units of a single resource (e.g. 2 wood) is split into unit productions,
+/// while a choice between resources is kept as is.
+fn split_production(production: ResourceArray) -> Vec<ResourceArray> {
+    let produced: Vec<_> = (0..7).filter(|&i| production[i] > 0).collect();
+    if let [resource] = produced[..] {
+        let mut unit = [0; 7];
+        unit[resource] = 1;
+        vec![unit; production[resource] as usize]
+    } else {
+        vec![production]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PlayerAction {
     /// Choose to play i-th card in the hand
     PickCard(usize),
+    /// Choose to build the next wonder stage with the i-th card in the hand
+    BuildWonderStage(usize),
+    /// Choose to discard the i-th card in the hand for 3 gold
+    DiscardCard(usize),
+    /// Choose to build the i-th card in the hand for free, once per age (Olympia)
+    BuildForFree(usize),
+    /// Build the i-th card of the discard pile for free (Halicarnassus)
+    BuildFromDiscard(usize),
+    /// Copy a guild of an adjacent player: (relative player position, card index on their board) (Olympia)
+    CopyGuild(usize, usize),
     /// Cancel playing i-th card
     CancelCard,
     /// Choose to get a resource from a player: (relative player position, resource production id, resource id)
     GetResource(usize, usize, usize),
+    /// Automatically choose the cheapest resources for the current card
+    ChooseCheapestResources,
     /// Validate playing current card
     Validate,
     /// Go back to cost selection screen
     Unvalidate,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Play {
     NoAction,
     ChoosingResources {
         card_index: usize,
+        target: PlayTarget,
         resource_allocation: ResourceAllocation,
         verdict: ResourceAllocationVerdict,
     },
     ChoseResources {
         card_index: usize,
+        target: PlayTarget,
         resource_allocation: ResourceAllocation,
     },
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// What the played card is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayTarget {
+    /// Build the card on the board.
+    Board,
+    /// Build the card on the board for free, using the wonder power of the age.
+    BoardForFree,
+    /// Build the next stage of the wonder.
+    WonderStage,
+    /// Discard the card for 3 gold.
+    Discard,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceAllocationVerdict {
     extra_resources: ResourceArray,
     missing_resources: ResourceArray,
     missing_gold: u32,
+    /// The card is built for free thanks to chaining.
+    free_construction: bool,
 }
 
 impl ResourceAllocationVerdict {
     pub fn is_valid(&self) -> bool {
-        self.extra_resources.iter().filter(|&&x| x != 0).count() == 0 && self.missing_resources.iter().filter(|&&x| x != 0).count() == 0 && self.missing_gold == 0
+        self.has_right_resources() && self.missing_gold == 0
+    }
+
+    /// Check that the allocation produces exactly the resources of the cost.
+    pub fn has_right_resources(&self) -> bool {
+        self.extra_resources.iter().filter(|&&x| x != 0).count() == 0 && self.missing_resources.iter().filter(|&&x| x != 0).count() == 0
+    }
+}
+
+pub type ResourceAllocation = [Vec<usize>; 3];
+
+/// A decision that a player can make, see `Game::legal_actions`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum LegalAction {
+    /// Use the i-th card of the hand for some target, choosing the resources of the allocation.
+    Play {
+        card_index: usize,
+        target: PlayTarget,
+        resource_allocation: ResourceAllocation,
+    },
+    /// Build the i-th card of the discard pile.
+    BuildFromDiscard(usize),
+    /// Copy a guild of an adjacent player: (relative player position, card index on their board).
+    CopyGuild(usize, usize),
+}
+
+impl LegalAction {
+    /// The sequence of actions that makes this decision.
+    pub fn player_actions(&self) -> Vec<PlayerAction> {
+        match self {
+            LegalAction::Play { card_index, target, resource_allocation } => {
+                let card_index = *card_index;
+                let first_action = match target {
+                    PlayTarget::Discard => return vec![PlayerAction::DiscardCard(card_index)],
+                    PlayTarget::BoardForFree => return vec![PlayerAction::BuildForFree(card_index)],
+                    PlayTarget::Board => PlayerAction::PickCard(card_index),
+                    PlayTarget::WonderStage => PlayerAction::BuildWonderStage(card_index),
+                };
+                let mut actions = vec![first_action];
+                for (player, productions) in resource_allocation.iter().enumerate() {
+                    for (production, &resource) in productions.iter().enumerate() {
+                        if resource > 0 {
+                            actions.push(PlayerAction::GetResource(player, production, resource));
+                        }
+                    }
+                }
+                actions.push(PlayerAction::Validate);
+                actions
+            },
+            LegalAction::BuildFromDiscard(card_index) => vec![PlayerAction::BuildFromDiscard(*card_index)],
+            LegalAction::CopyGuild(side, card_index) => vec![PlayerAction::CopyGuild(*side, *card_index)],
+        }
     }
 }
 
-pub type ResourceAllocation = [Vec<usize>; 3];
\ No newline at end of file
+/// The decision the game is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// Every player chooses what to do with a card of their hand.
+    PlayingCards,
+    /// A player plays the last card of their hand at the end of the age (Babylon).
+    PlayingSeventhCard(usize),
+    /// A player builds a card from the discard pile (Halicarnassus).
+    BuildingFromDiscard(usize),
+    /// A player copies a guild of an adjacent player at the end of the game (Olympia).
+    CopyingGuild(usize),
+}
+
+/// What a player is allowed to know about a game: the public boards, and their own hand and play.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerView {
+    /// The position of the player.
+    pub player: usize,
+    pub age: usize,
+    pub phase: Phase,
+    pub players: Vec<PublicPlayerData>,
+    pub hand: Vec<Card>,
+    pub play: Play,
+    /// The discard pile, only while the player is building a card from it.
+    pub discard_pile: Option<Vec<Card>>,
+    /// The decisions that the player can make, as hints for the interface.
+    pub legal_actions: Vec<LegalAction>,
+    pub scores: Option<Vec<Score>>,
+}
+
+/// What every player can see of a player.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicPlayerData {
+    pub board_cards: Vec<Card>,
+    pub resource_productions: [Vec<ResourceArray>; 3],
+    pub resource_costs: [ResourceArray; 3],
+    pub gold: u32,
+    pub wonder: Wonder,
+    pub wonder_stages_built: usize,
+    pub military_tokens: Vec<i32>,
+    pub copied_guild: Option<Card>,
+    /// The number of cards in the hand.
+    pub hand_size: usize,
+    /// Whether the play of the current turn is validated.
+    pub ready: bool,
+}
+
+/// The version of the snapshot format, to be increased whenever the format changes.
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+/// The complete state of a game, that can be saved and resumed later.
+/// The random number generators are derived from the seed of the game for every age, so the seed and the age
+/// are the whole random state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub version: u32,
+    pub game: Game,
+}
+
+/// The reason why a snapshot can't be resumed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The snapshot was saved with another version of the format.
+    UnsupportedVersion(u32),
+    /// The number of players or plays doesn't match the player count.
+    WrongPlayerCount,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::UnsupportedVersion(version) => write!(f, "unsupported snapshot version {}, expected {}", version, SNAPSHOT_VERSION),
+            SnapshotError::WrongPlayerCount => write!(f, "the players don't match the player count"),
+        }
+    }
+}
+
+/// The reason why an action was refused.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ActionError {
+    /// The player or relative player position doesn't exist.
+    InvalidPlayer,
+    /// There is no card with this index in the hand, the discard pile or the board.
+    InvalidCardIndex,
+    /// The production doesn't exist or can't produce the resource.
+    InvalidResource,
+    /// The action doesn't follow the current play, e.g. validating before picking a card.
+    UnexpectedAction,
+    /// The game is not waiting for this action from this player.
+    WrongPhase,
+    /// A card with the same name is already on the player's board.
+    AlreadyBuilt,
+    /// All the stages of the wonder are already built.
+    WonderComplete,
+    /// The wonder power was not built yet, or was already used during this age.
+    PowerUnavailable,
+    /// Only guilds can be copied.
+    NotAGuild,
+    /// The productions of the player and the neighbours can't pay for the card.
+    MissingResources,
+    /// The chosen resources don't match the cost of the card.
+    InvalidResourceAllocation,
+    /// The player can't pay the gold for the card and the resources.
+    NotEnoughGold,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::play_random_round;
+
+    /// Check that the decision was taken into account: the play is validated, or the game moved on.
+    fn was_accepted(before: &Game, after: &Game, player: usize) -> bool {
+        let validated = match after.plays[player] {
+            Play::ChoseResources {..} => true,
+            Play::NoAction | Play::ChoosingResources {..} => false,
+        };
+        validated
+            || after.phase != before.phase
+            || after.players[player].hand_cards.len() != before.players[player].hand_cards.len()
+            || after.scores.is_some()
+    }
+
+    #[test]
+    fn legal_actions_are_accepted() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for player_count in 3..=7 {
+            let mut game = Game::new(player_count, rng.gen());
+            while game.get_scores().is_none() {
+                for player in 0..player_count {
+                    for legal_action in game.legal_actions(player) {
+                        let mut after = game.clone();
+                        for action in legal_action.player_actions() {
+                            assert!(after.perform_action(player, action).is_ok());
+                        }
+                        assert!(was_accepted(&game, &after, player), "{:?} was not accepted", legal_action);
+                    }
+                }
+                assert!(play_random_round(&mut game, &mut rng), "no player can play");
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let card_names = |game: &Game| -> Vec<Vec<String>> {
+            game.players.iter().map(|player| player.hand_cards.iter().map(|card| card.name.clone()).collect()).collect()
+        };
+        let wonder_names = |game: &Game| -> Vec<String> {
+            game.players.iter().map(|player| format!("{} {:?}", player.wonder.name, player.wonder.side)).collect()
+        };
+        let game = Game::new(7, 1234);
+        let same_game = Game::new(7, 1234);
+        let other_game = Game::new(7, 4321);
+        assert_eq!(card_names(&game), card_names(&same_game));
+        assert_eq!(wonder_names(&game), wonder_names(&same_game));
+        assert_ne!(card_names(&game), card_names(&other_game));
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut game = Game::new(4, 7);
+        // Play a few turns, and leave some plays pending
+        for _ in 0..10 {
+            play_random_round(&mut game, &mut rng);
+        }
+        game.perform_action(0, PlayerAction::DiscardCard(0)).unwrap();
+
+        let json = serde_json::to_string(&game.snapshot()).unwrap();
+        let restored = Game::from_snapshot(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(serde_json::to_string(&restored.snapshot()).unwrap(), json);
+    }
+
+    #[test]
+    fn player_views_keep_secrets_server_side() {
+        let game = Game::new(3, 1234);
+        for player in 0..3 {
+            let view = serde_json::to_value(game.player_view(player)).unwrap();
+            // Knowing the seed would reveal every hand and every future deal
+            assert!(view.get("seed").is_none());
+            assert!(view.get("journal").is_none());
+            for public_data in view["players"].as_array().unwrap() {
+                assert!(public_data.get("hand_cards").is_none());
+            }
+            assert_eq!(view["hand"], serde_json::to_value(&game.players[player].hand_cards).unwrap());
+        }
+    }
+
+    #[test]
+    fn player_views_list_the_legal_actions_of_the_turn() {
+        let mut game = Game::new(3, 10);
+        let first_turn = game.legal_actions(0);
+        assert_eq!(game.player_view(0).legal_actions, first_turn);
+        game.perform_action(0, PlayerAction::PickCard(0)).unwrap();
+        assert!(game.player_view(0).legal_actions.is_empty());
+        game.perform_action(0, PlayerAction::CancelCard).unwrap();
+        assert_eq!(game.player_view(0).legal_actions, first_turn);
+
+        discard_first_cards(&mut game);
+        let second_turn = game.legal_actions(0);
+        assert_ne!(second_turn, first_turn);
+        assert_eq!(game.player_view(0).legal_actions, second_turn);
+    }
+
+    #[test]
+    fn snapshot_version_is_checked() {
+        let mut snapshot = Game::new(3, 0).snapshot();
+        snapshot.version += 1;
+        assert_eq!(Game::from_snapshot(snapshot).unwrap_err(), SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1));
+    }
+
+    #[test]
+    fn state_hash_follows_the_state() {
+        let mut game = Game::new(3, 5);
+        let hash = game.state_hash();
+        assert_eq!(game.clone().state_hash(), hash);
+        assert_eq!(Game::new(3, 5).state_hash(), hash);
+        game.players[0].gold += 1;
+        assert_ne!(game.state_hash(), hash);
+    }
+
+    #[test]
+    fn automatic_play_finishes_the_game() {
+        let mut game = Game::new(5, 11);
+        while game.get_scores().is_none() {
+            for player in 0..5 {
+                game.play_automatically(player).unwrap();
+            }
+        }
+    }
+
+    /// Give a wonder with a single free stage granting a power to a player.
+    fn give_power(game: &mut Game, player: usize, power: CardEffect, built: bool) {
+        game.players[player].wonder = Wonder {
+            name: format!("{:?}", power),
+            side: WonderSide::A,
+            starting_resource: [0; 7],
+            stages: vec![WonderStage { resource_cost: [0; 7], effects: vec![power] }],
+        };
+        game.players[player].wonder_stages_built = if built { 1 } else { 0 };
+    }
+
+    /// Make the first card of a hand free to build.
+    fn make_first_card_free(game: &mut Game, player: usize) {
+        let card = &mut game.players[player].hand_cards[0];
+        card.gold_cost = 0;
+        card.resource_cost = [0; 7];
+    }
+
+    /// Make every player that has to play a card discard their first card.
+    fn discard_first_cards(game: &mut Game) {
+        let players: Vec<_> = (0..game.player_count).filter(|&player| game.is_waiting_for(player)).collect();
+        for player in players {
+            game.perform_action(player, PlayerAction::DiscardCard(0)).unwrap();
+        }
+    }
+
+    /// Discard until the last turn of the age, when every hand has two cards.
+    fn discard_until_last_turn(game: &mut Game) {
+        while game.players[0].hand_cards.len() > 2 {
+            discard_first_cards(game);
+        }
+    }
+
+    fn hand_sizes(game: &Game) -> Vec<usize> {
+        game.players.iter().map(|player| player.hand_cards.len()).collect()
+    }
+
+    #[test]
+    fn babylon_discards_the_seventh_card() {
+        let mut game = Game::new(3, 1);
+        give_power(&mut game, 0, CardEffect::PlaySeventhCard, true);
+        discard_until_last_turn(&mut game);
+        discard_first_cards(&mut game);
+        assert_eq!(game.phase, Phase::PlayingSeventhCard(0));
+        assert_eq!(hand_sizes(&game), vec![1, 1, 1]);
+        assert_eq!(game.perform_action(1, PlayerAction::DiscardCard(0)).unwrap_err(), ActionError::WrongPhase);
+
+        let gold = game.players[0].gold;
+        game.perform_action(0, PlayerAction::DiscardCard(0)).unwrap();
+        assert_eq!(game.players[0].gold, gold + 3);
+        assert_eq!(game.discard_pile.len(), 21);
+        assert_eq!((game.age, game.phase), (1, Phase::PlayingCards));
+        assert_eq!(hand_sizes(&game), vec![7, 7, 7]);
+    }
+
+    #[test]
+    fn babylon_builds_the_seventh_card() {
+        let mut game = Game::new(3, 2);
+        give_power(&mut game, 0, CardEffect::PlaySeventhCard, true);
+        discard_until_last_turn(&mut game);
+        discard_first_cards(&mut game);
+        assert_eq!(game.phase, Phase::PlayingSeventhCard(0));
+
+        make_first_card_free(&mut game, 0);
+        game.perform_action(0, PlayerAction::PickCard(0)).unwrap();
+        game.perform_action(0, PlayerAction::Validate).unwrap();
+        assert_eq!(game.players[0].board_cards.len(), 1);
+        assert_eq!(game.discard_pile.len(), 20);
+        assert_eq!((game.age, game.phase), (1, Phase::PlayingCards));
+    }
+
+    #[test]
+    fn halicarnassus_with_an_empty_discard_pile() {
+        let mut game = Game::new(3, 3);
+        give_power(&mut game, 1, CardEffect::BuildFromDiscard, false);
+        for player in 0..3 {
+            make_first_card_free(&mut game, player);
+        }
+        game.perform_action(0, PlayerAction::PickCard(0)).unwrap();
+        game.perform_action(0, PlayerAction::Validate).unwrap();
+        game.perform_action(1, PlayerAction::BuildWonderStage(0)).unwrap();
+        game.perform_action(1, PlayerAction::Validate).unwrap();
+        game.perform_action(2, PlayerAction::PickCard(0)).unwrap();
+        game.perform_action(2, PlayerAction::Validate).unwrap();
+        assert!(game.discard_pile.is_empty());
+        assert_eq!(game.phase, Phase::PlayingCards);
+        assert_eq!(hand_sizes(&game), vec![6, 6, 6]);
+    }
+
+    #[test]
+    fn halicarnassus_builds_from_the_discard_pile() {
+        let mut game = Game::new(3, 4);
+        give_power(&mut game, 1, CardEffect::BuildFromDiscard, false);
+        game.perform_action(1, PlayerAction::BuildWonderStage(0)).unwrap();
+        game.perform_action(1, PlayerAction::Validate).unwrap();
+        discard_first_cards(&mut game);
+        assert_eq!(game.phase, Phase::BuildingFromDiscard(1));
+        assert_eq!(game.discard_pile.len(), 2);
+        assert_eq!(game.perform_action(0, PlayerAction::PickCard(0)).unwrap_err(), ActionError::WrongPhase);
+
+        game.perform_action(1, PlayerAction::BuildFromDiscard(0)).unwrap();
+        assert_eq!(game.players[1].board_cards.len(), 1);
+        assert_eq!(game.discard_pile.len(), 1);
+        assert_eq!(game.phase, Phase::PlayingCards);
+        assert_eq!(hand_sizes(&game), vec![6, 6, 6]);
+    }
+
+    #[test]
+    fn turn_is_resolved_after_the_discard_build() {
+        let mut game = Game::new(3, 4);
+        give_power(&mut game, 1, CardEffect::BuildFromDiscard, false);
+        game.perform_action(1, PlayerAction::BuildWonderStage(0)).unwrap();
+        game.perform_action(1, PlayerAction::Validate).unwrap();
+        discard_first_cards(&mut game);
+        assert_eq!(game.resolved_turns(), 0);
+
+        game.perform_action(1, PlayerAction::BuildFromDiscard(0)).unwrap();
+        assert_eq!(game.resolved_turns(), 1);
+        assert_eq!(game.journal().entries.last(), Some(&JournalEntry::TurnResolved { age: 0, state_hash: game.state_hash() }));
+    }
+
+    #[test]
+    fn halicarnassus_on_the_last_turn_of_the_game() {
+        let mut game = Game::new(3, 5);
+        give_power(&mut game, 1, CardEffect::BuildFromDiscard, false);
+        while game.age < 2 {
+            discard_first_cards(&mut game);
+        }
+        discard_until_last_turn(&mut game);
+        game.perform_action(1, PlayerAction::BuildWonderStage(0)).unwrap();
+        game.perform_action(1, PlayerAction::Validate).unwrap();
+        discard_first_cards(&mut game);
+        // The last cards are already discarded, but the game waits for Halicarnassus before scoring
+        assert_eq!(game.phase, Phase::BuildingFromDiscard(1));
+        assert_eq!(hand_sizes(&game), vec![0, 0, 0]);
+        assert!(game.get_scores().is_none());
+
+        let last_card = game.discard_pile.len() - 1;
+        let name = game.discard_pile[last_card].name.clone();
+        game.perform_action(1, PlayerAction::BuildFromDiscard(last_card)).unwrap();
+        assert!(game.players[1].has_built(&name));
+        assert!(game.get_scores().is_some());
+    }
+
+    #[test]
+    fn babylon_and_halicarnassus_in_the_same_turn() {
+        let mut game = Game::new(3, 6);
+        give_power(&mut game, 0, CardEffect::PlaySeventhCard, true);
+        give_power(&mut game, 1, CardEffect::BuildFromDiscard, false);
+        discard_until_last_turn(&mut game);
+        game.perform_action(1, PlayerAction::BuildWonderStage(0)).unwrap();
+        game.perform_action(1, PlayerAction::Validate).unwrap();
+        discard_first_cards(&mut game);
+
+        // Babylon plays first, so that Halicarnassus can build the seventh card
+        assert_eq!(game.phase, Phase::PlayingSeventhCard(0));
+        let seventh_card = game.players[0].hand_cards[0].name.clone();
+        game.perform_action(0, PlayerAction::DiscardCard(0)).unwrap();
+        assert_eq!(game.phase, Phase::BuildingFromDiscard(1));
+        let index = game.discard_pile.iter().position(|card| card.name == seventh_card).unwrap();
+        game.perform_action(1, PlayerAction::BuildFromDiscard(index)).unwrap();
+        assert!(game.players[1].has_built(&seventh_card));
+        assert_eq!((game.age, game.phase), (1, Phase::PlayingCards));
+        assert_eq!(hand_sizes(&game), vec![7, 7, 7]);
+    }
+
+    #[test]
+    fn olympia_free_build_resets_every_age() {
+        let mut game = Game::new(3, 7);
+        give_power(&mut game, 0, CardEffect::FreeBuildOncePerAge, true);
+        game.perform_action(0, PlayerAction::BuildForFree(0)).unwrap();
+        discard_first_cards(&mut game);
+        assert_eq!(game.players[0].board_cards.len(), 1);
+        assert_eq!(game.perform_action(0, PlayerAction::BuildForFree(0)).unwrap_err(), ActionError::PowerUnavailable);
+
+        while game.age < 1 {
+            discard_first_cards(&mut game);
+        }
+        game.perform_action(0, PlayerAction::BuildForFree(0)).unwrap();
+        discard_first_cards(&mut game);
+        assert_eq!(game.players[0].board_cards.len(), 2);
+    }
+
+    #[test]
+    fn olympia_copies_a_guild_only_if_a_neighbour_has_one() {
+        let mut game = Game::new(3, 8);
+        give_power(&mut game, 0, CardEffect::CopyGuild, true);
+        while game.get_scores().is_none() {
+            assert_eq!(game.phase, Phase::PlayingCards);
+            discard_first_cards(&mut game);
+        }
+        assert!(game.players[0].copied_guild.is_none());
+
+        let mut game = Game::new(3, 8);
+        give_power(&mut game, 0, CardEffect::CopyGuild, true);
+        let guild = game.card_registry.generate_cards(2, 3, &mut StdRng::seed_from_u64(0)).into_iter()
+            .find(|card| card.color == COLOR_PURPLE)
+            .unwrap();
+        game.players[1].board_cards.push(guild.clone());
+        while game.get_scores().is_none() && game.phase == Phase::PlayingCards {
+            discard_first_cards(&mut game);
+        }
+        assert_eq!(game.phase, Phase::CopyingGuild(0));
+        game.perform_action(0, PlayerAction::CopyGuild(RIGHT_PLAYER, 0)).unwrap();
+        assert_eq!(game.players[0].copied_guild.as_ref().map(|card| &card.name), Some(&guild.name));
+        assert!(game.get_scores().is_some());
+    }
+
+    #[test]
+    fn automatic_play_resolves_the_wonder_powers() {
+        let mut game = Game::new(3, 9);
+        give_power(&mut game, 0, CardEffect::PlaySeventhCard, true);
+        give_power(&mut game, 1, CardEffect::BuildFromDiscard, false);
+        discard_until_last_turn(&mut game);
+        game.perform_action(1, PlayerAction::BuildWonderStage(0)).unwrap();
+        game.perform_action(1, PlayerAction::Validate).unwrap();
+        discard_first_cards(&mut game);
+
+        // Only the player with the decision to make is played for
+        assert_eq!(game.phase, Phase::PlayingSeventhCard(0));
+        game.play_automatically(1).unwrap();
+        assert_eq!(game.phase, Phase::PlayingSeventhCard(0));
+        game.play_automatically(0).unwrap();
+        assert_eq!(game.phase, Phase::BuildingFromDiscard(1));
+        game.play_automatically(0).unwrap();
+        assert_eq!(game.phase, Phase::BuildingFromDiscard(1));
+        game.play_automatically(1).unwrap();
+        assert_eq!((game.age, game.phase), (1, Phase::PlayingCards));
+        assert_eq!(game.players[1].board_cards.len(), 1);
+    }
+
+    #[test]
+    fn military_tokens_at_the_end_of_each_age() {
+        let mut game = Game::new(3, 12);
+        let red_card = |shields| Card {
+            color: COLOR_RED,
+            name: format!("Shields {}", shields),
+            gold_cost: 0,
+            resource_cost: [0; 7],
+            effect: CardEffect::Shields(shields),
+            chaining_targets: vec![],
+            chaining_sources: vec![],
+        };
+        // Players 0 and 1 are tied, and both beat player 2
+        game.players[0].board_cards.push(red_card(2));
+        game.players[1].board_cards.push(red_card(2));
+        while game.age < 1 {
+            discard_first_cards(&mut game);
+        }
+        assert_eq!(game.players[0].military_tokens, vec![1]);
+        assert_eq!(game.players[1].military_tokens, vec![1]);
+        assert_eq!(game.players[2].military_tokens, vec![-1, -1]);
+
+        while game.get_scores().is_none() {
+            discard_first_cards(&mut game);
+        }
+        assert_eq!(game.players[0].military_tokens, vec![1, 3, 5]);
+        assert_eq!(game.players[2].military_tokens, vec![-1; 6]);
+        let military: Vec<_> = game.get_scores().unwrap().iter().map(|score| score.military).collect();
+        assert_eq!(military, vec![9, 9, -6]);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::game::{ActionError, Game, PlayerAction};
+
+/// Everything that happened in a game, enough to replay it from its seed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Journal {
+    pub player_count: usize,
+    pub seed: u64,
+    pub entries: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JournalEntry {
+    /// A player action that was accepted.
+    Action {
+        player: usize,
+        action: PlayerAction,
+    },
+    /// The plays of a turn were resolved, leaving the game in a state with this hash.
+    TurnResolved {
+        age: usize,
+        state_hash: u64,
+    },
+}
+
+/// The reason why a replay doesn't match the journal.
+#[derive(Debug, Clone)]
+pub enum ReplayError {
+    /// The action of some entry was refused.
+    ActionRefused(usize, ActionError),
+    /// The replayed game recorded different entries after the action of some entry,
+    /// e.g. a turn resolution leading to another state.
+    Diverged(usize),
+    /// The replayed game ended in a different state.
+    FinalStateMismatch,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::ActionRefused(entry, error) => write!(f, "the action of entry {} was refused: {:?}", entry, error),
+            ReplayError::Diverged(entry) => write!(f, "the replay diverged after entry {}", entry),
+            ReplayError::FinalStateMismatch => write!(f, "the replay ended in a different state"),
+        }
+    }
+}
+
+impl Journal {
+    pub fn new(player_count: usize, seed: u64) -> Journal {
+        Journal {
+            player_count,
+            seed,
+            entries: vec![],
+        }
+    }
+
+    /// Rebuild the game from the seed by performing the actions again,
+    /// checking that the turns are resolved at the same moments.
+    pub fn replay(&self) -> Result<Game, ReplayError> {
+        let mut game = Game::new(self.player_count, self.seed);
+        for (i, entry) in self.entries.iter().enumerate() {
+            if let JournalEntry::Action { player, action } = entry {
+                game.perform_action(*player, action.clone()).map_err(|error| ReplayError::ActionRefused(i, error))?;
+                let replayed_entries = &game.journal().entries;
+                if replayed_entries.len() > self.entries.len() || replayed_entries[..] != self.entries[..replayed_entries.len()] {
+                    return Err(ReplayError::Diverged(i));
+                }
+            }
+        }
+        if game.journal() != self {
+            return Err(ReplayError::Diverged(self.entries.len()));
+        }
+        Ok(game)
+    }
+}
+
+/// Replay a game from its journal and check that it ends in exactly the same state.
+pub fn verify_replay(game: &Game) -> Result<(), ReplayError> {
+    let replayed = game.journal().replay()?;
+    if replayed.serialized_state() == game.serialized_state() {
+        Ok(())
+    } else {
+        Err(ReplayError::FinalStateMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::play_random_game;
+
+    #[test]
+    fn replay_matches_the_game() {
+        for player_count in 3..=7 {
+            let game = play_random_game(player_count, player_count as u64);
+            verify_replay(&game).unwrap();
+        }
+    }
+
+    #[test]
+    fn tampered_journal_diverges() {
+        let game = play_random_game(3, 0);
+        let mut journal = game.journal().clone();
+        let first_turn = journal.entries.iter().position(|entry| match entry {
+            JournalEntry::TurnResolved {..} => true,
+            JournalEntry::Action {..} => false,
+        }).unwrap();
+        // Without the action that completed the first turn, it is resolved later
+        journal.entries.remove(first_turn - 1);
+        assert!(journal.replay().is_err());
+    }
+}
@@ -1,9 +1,20 @@
 use actix::prelude::*;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use crate::connection::PlayerConnection;
-use crate::game::{Game as GameState, PlayerAction};
-use crate::messages::{ActiveGameInfo, ConnectInfo, PlayerInfo, FromPlayer, GameInfo, ToPlayer, ToServer};
+use crate::game::{seeded_rng, ActionError, Game as GameState, GameSnapshot, Phase, PlayerAction, RandomStep};
+use crate::journal::verify_replay;
+use crate::messages::{ActiveGameInfo, ConnectInfo, PlayerInfo, FromPlayer, GameInfo, GameOptions, ScoreInfo, ToPlayer, ToServer};
+
+/// The directory where the running games are saved, to resume them when the server restarts.
+const SAVE_DIRECTORY: &str = "saved_games";
+/// The directory where the finished games are kept with their journal, for post-mortems and regression tests.
+const ARCHIVE_DIRECTORY: &str = "finished_games";
 
 #[derive(Debug)]
 pub struct Lobby {
@@ -13,12 +24,74 @@ pub struct Lobby {
 
 impl Lobby {
     pub fn new() -> Self {
-        Self {
+        let mut lobby = Self {
             games: HashMap::new(),
             players: HashMap::new(),
+        };
+        lobby.restore_games();
+        lobby
+    }
+
+    /// Resume the games that were running when the server stopped.
+    /// The players join them again with the same name.
+    fn restore_games(&mut self) {
+        let entries = match fs::read_dir(SAVE_DIRECTORY) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let saved_game = match SavedGame::load(&path) {
+                Ok(saved_game) => saved_game,
+                Err(error) => {
+                    println!("Couldn't load saved game {:?}: {}", path, error);
+                    continue;
+                },
+            };
+            let state = match GameState::from_snapshot(saved_game.snapshot) {
+                Ok(state) => state,
+                Err(error) => {
+                    println!("Couldn't resume game {}: {}", saved_game.name, error);
+                    continue;
+                },
+            };
+            // The journal must lead to the same state, otherwise the save is corrupted
+            if let Err(error) = verify_replay(&state) {
+                println!("Couldn't resume game {}: {}", saved_game.name, error);
+                continue;
+            }
+            println!("Resuming game {}", saved_game.name);
+            self.games.insert(saved_game.name.clone(), Game {
+                name: saved_game.name,
+                player_count: state.player_count(),
+                seed: state.seed(),
+                turn_duration: saved_game.turn_seconds.map(Duration::from_secs),
+                turn_start: None,
+                players: saved_game.player_names.iter().map(|name| (name.clone(), None)).collect(),
+                state: Some(state),
+                player_ids: saved_game.player_names.iter().enumerate().map(|(i, name)| (name.clone(), i)).collect(),
+                player_names: saved_game.player_names,
+            });
         }
     }
 
+    fn create_game(&mut self, options: GameOptions) {
+        let GameOptions { name, player_count, seed, turn_seconds } = options;
+        // TODO: Ban cancer game names
+        // Make sure the game does not exist yet
+        self.games.entry(name.clone()).or_insert(Game {
+            name,
+            player_count,
+            seed: seed.unwrap_or_else(|| thread_rng().gen()),
+            turn_duration: turn_seconds.map(Duration::from_secs),
+            turn_start: None,
+            players: HashMap::new(),
+            state: None,
+            player_ids: HashMap::new(),
+            player_names: Vec::new(),
+        });
+        self.broadcast_games();
+    }
+
     fn broadcast_games(&self) {
         let games : Vec<_> = self.games.iter().map(|(name, game)| {
             GameInfo {
@@ -36,6 +109,14 @@ impl Lobby {
 
 impl Actor for Lobby {
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_interval(Duration::from_secs(1), |lobby, _ctx| {
+            for game in lobby.games.values_mut() {
+                game.update_turn_timer();
+            }
+        });
+    }
 }
 
 impl Handler<ToServer> for Lobby {
@@ -61,17 +142,10 @@ impl Handler<ToServer> for Lobby {
             },
             ToServer::PlayerMessage(addr, msg) => match msg {
                 FromPlayer::CreateGame(name, player_count) => {
-                    // TODO: Ban cancer game names
-                    // Make sure the game does not exist yet
-                    self.games.entry(name.clone()).or_insert(Game {
-                        name,
-                        player_count,
-                        players: HashMap::new(),
-                        state: None,
-                        player_ids: HashMap::new(),
-                        player_names: Vec::new(),
-                    });
-                    self.broadcast_games();
+                    self.create_game(GameOptions { name, player_count, seed: None, turn_seconds: None });
+                },
+                FromPlayer::CreateCustomGame(options) => {
+                    self.create_game(options);
                 },
                 FromPlayer::Connect(ConnectInfo { game_name, player_name }) => {
                     // Make sure the game exists
@@ -106,6 +180,12 @@ impl Handler<ToServer> for Lobby {
 struct Game {
     pub name: String,
     pub player_count: usize,
+    /// The seed of the game, which determines the seats, the wonders and the cards.
+    pub seed: u64,
+    /// The time limit of every turn, if any.
+    pub turn_duration: Option<Duration>,
+    /// When the current decision started to be awaited, with the (resolved turns, phase) that identify it.
+    pub turn_start: Option<((usize, Phase), Instant)>,
     pub players: HashMap<String, ConnectedPlayer>,
     pub state: Option<GameState>,
     pub player_ids: HashMap<String, usize>,
@@ -169,13 +249,13 @@ impl Game {
 
         // Randomly assign positions to players
         let mut names = self.get_connected_players();
-        names.shuffle(&mut thread_rng());
+        names.shuffle(&mut seeded_rng(self.seed, RandomStep::Seats));
         self.player_names = names;
         for (i, name) in self.player_names.iter().enumerate() {
             self.player_ids.insert(name.to_string(), i);
         }
         
-        self.state = Some(GameState::new(self.player_count));
+        self.state = Some(GameState::new(self.player_count, self.seed));
     }
 
     fn get_connected_players(&self) -> Vec<String> {
@@ -195,35 +275,194 @@ impl Game {
     fn broadcast_game_info(&self) {
         println!("broadcasting game info");
         let connected_players = self.get_connected_players();
-        let agi = ActiveGameInfo { name: self.name.clone(), player_count: self.player_count, connected_players, game: self.state.clone(), };
         for (player_name, cp) in self.players.iter() {
             if let Some(addr) = cp {
                 let i = self.player_ids.get(player_name);
+                let view = self.state.as_ref().map(|game| game.player_view(*i.expect("Unknown player name")));
                 addr.do_send(ToPlayer::ActiveGame(
                     PlayerInfo {
                         player_name: player_name.clone(),
-                        cards: self.state.as_ref().map(|game| game.players[*i.expect("Unknown player name")].hand_cards.clone()),
-                        play: self.state.as_ref().map(|game| game.plays[*i.expect("Unknown player name")].clone()),
+                        cards: view.as_ref().map(|view| view.hand.clone()),
+                        play: view.as_ref().map(|view| view.play.clone()),
                     },
-                    agi.clone()
+                    ActiveGameInfo {
+                        name: self.name.clone(),
+                        player_count: self.player_count,
+                        connected_players: connected_players.clone(),
+                        game: view,
+                    }
                 ));
             }
         }
     }
 
+    /// Send the final scores to the players, once the game is over.
+    fn broadcast_scores(&self) {
+        if let Some(scores) = self.state.as_ref().and_then(|game| game.get_scores()) {
+            let score_infos = self.player_names.iter().zip(scores).map(|(player_name, score)| {
+                ScoreInfo {
+                    player_name: player_name.clone(),
+                    score: score.clone(),
+                }
+            }).collect();
+            let message = ToPlayer::GameOver(score_infos);
+            for (_, cp) in self.players.iter() {
+                if let Some(addr) = cp {
+                    addr.do_send(message.clone());
+                }
+            }
+        }
+    }
+
+    /// Tell the players how much time is left, and decide for the late players once the time is over.
+    pub fn update_turn_timer(&mut self) {
+        let turn_duration = match self.turn_duration {
+            Some(turn_duration) => turn_duration,
+            None => return,
+        };
+        let game = match self.state.as_mut() {
+            Some(game) if game.get_scores().is_none() => game,
+            _ => return,
+        };
+        let turn = (game.resolved_turns(), game.phase());
+        let now = Instant::now();
+        let turn_start = match self.turn_start {
+            Some((started_turn, turn_start)) if started_turn == turn => turn_start,
+            _ => {
+                self.turn_start = Some((turn, now));
+                now
+            },
+        };
+
+        let elapsed = now.duration_since(turn_start);
+        if elapsed < turn_duration {
+            let message = ToPlayer::RemainingTime((turn_duration - elapsed).as_secs());
+            for addr in self.players.values().flatten() {
+                addr.do_send(message.clone());
+            }
+        } else {
+            // Only decide for the players that are late, the next turn may start in the meantime
+            let late_players: Vec<_> = (0..self.player_count).filter(|&player| game.is_waiting_for(player)).collect();
+            for player in late_players {
+                if (game.resolved_turns(), game.phase()) != turn {
+                    break;
+                }
+                if let Err(error) = game.play_automatically(player) {
+                    println!("Couldn't play automatically for player {}: {:?}", player, error);
+                }
+            }
+            let finished = game.get_scores().is_some();
+            let turn_resolved = (game.resolved_turns(), game.phase()) != turn;
+            if turn_resolved || finished {
+                self.save();
+            }
+            self.broadcast_game_info();
+            if finished {
+                self.broadcast_scores();
+            }
+        }
+    }
+
     pub fn perform_action(&mut self, player: Addr<PlayerConnection>, action: PlayerAction) {
+        let mut finished = false;
+        let mut turn_resolved = false;
         for (name, cp) in self.players.iter() {
             if let Some(addr) = cp {
                 if *addr == player {
                     if let Some(game) = self.state.as_mut() {
                         let player_id = self.player_ids.get(name).expect("Unknown player name");
-                        println!("name {:?} was mapped to id {:?}", &name, player_id);
-                        game.perform_action(*player_id, action.clone());
+                        let was_over = game.get_scores().is_some();
+                        let turn = (game.resolved_turns(), game.phase());
+                        match game.perform_action(*player_id, action.clone()) {
+                            Ok(()) => turn_resolved = (game.resolved_turns(), game.phase()) != turn,
+                            Err(error) => addr.do_send(ToPlayer::Error(error)),
+                        }
+                        finished = !was_over && game.get_scores().is_some();
+                    } else {
+                        // The game has not started yet
+                        addr.do_send(ToPlayer::Error(ActionError::WrongPhase));
                     }
                 }
             };
         }
+        if turn_resolved || finished {
+            self.save();
+        }
         self.broadcast_game_info();
+        if finished {
+            self.broadcast_scores();
+        }
+    }
+
+    /// The name of the files where the game is saved.
+    fn file_name(&self) -> String {
+        // Game names are chosen by the players, so they are encoded to be safe file names
+        let encoded_name: String = self.name.bytes().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}.json", encoded_name)
+    }
+
+    /// Where the game is saved while it is running.
+    fn save_path(&self) -> PathBuf {
+        Path::new(SAVE_DIRECTORY).join(self.file_name())
+    }
+
+    /// Save the game if it is running, or move it to the archive once it is over.
+    /// It is only called when a turn or a phase ends, so the players redo the plays of an unfinished turn after a restart.
+    fn save(&self) {
+        let state = match &self.state {
+            Some(state) => state,
+            None => return,
+        };
+        let saved_game = SavedGame {
+            name: self.name.clone(),
+            player_names: self.player_names.clone(),
+            turn_seconds: self.turn_duration.map(|duration| duration.as_secs()),
+            snapshot: state.snapshot(),
+        };
+        let path = self.save_path();
+        let result = if state.get_scores().is_some() {
+            self.archive(&saved_game).and_then(|_| match fs::remove_file(&path) {
+                Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            })
+        } else {
+            fs::create_dir_all(SAVE_DIRECTORY).and_then(|_| saved_game.save(&path))
+        };
+        if let Err(error) = result {
+            println!("Couldn't save game {}: {}", self.name, error);
+        }
+    }
+
+    /// Keep a finished game, after checking that it can be replayed from its journal.
+    fn archive(&self, saved_game: &SavedGame) -> io::Result<()> {
+        if let Err(error) = verify_replay(self.state.as_ref().expect("Only started games are archived")) {
+            println!("The replay of game {} diverged: {}", self.name, error);
+        }
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+        let path = Path::new(ARCHIVE_DIRECTORY).join(format!("{}-{}", timestamp, self.file_name()));
+        fs::create_dir_all(ARCHIVE_DIRECTORY).and_then(|_| saved_game.save(&path))
+    }
+}
+
+/// A running or finished game with everything the lobby needs to resume it.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedGame {
+    name: String,
+    /// The names of the players, in seat order.
+    player_names: Vec<String>,
+    turn_seconds: Option<u64>,
+    snapshot: GameSnapshot,
+}
+
+impl SavedGame {
+    fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, self).map_err(io::Error::from)
+    }
+
+    fn load(path: impl AsRef<Path>) -> io::Result<SavedGame> {
+        let file = BufReader::new(File::open(path)?);
+        serde_json::from_reader(file).map_err(io::Error::from)
     }
 }
 
@@ -8,8 +8,14 @@ use crate::lobby::Lobby;
 mod cards;
 mod connection;
 mod game;
+mod journal;
 mod lobby;
 mod messages;
+mod resources;
+mod scoring;
+#[cfg(test)]
+mod testing;
+mod wonders;
 
 #[derive(Debug, Clone)]
 struct AppState {
@@ -1,7 +1,8 @@
 use actix::prelude::*;
 use serde::{Serialize, Deserialize};
 use crate::connection::PlayerConnection;
-use crate::game::{Card, Game, Play, PlayerAction};
+use crate::game::{ActionError, Card, Play, PlayerAction, PlayerView};
+use crate::scoring::Score;
 
 #[derive(Message, Debug, Clone, Serialize)]
 pub enum ToPlayer {
@@ -9,6 +10,12 @@ pub enum ToPlayer {
     GameList(Vec<GameInfo>),
     /// Information about the current game
     ActiveGame(PlayerInfo, ActiveGameInfo),
+    /// Final scores of the current game, in player order
+    GameOver(Vec<ScoreInfo>),
+    /// The last action was refused
+    Error(ActionError),
+    /// Seconds left before the server decides for the players who didn't play
+    RemainingTime(u64),
 }
 
 #[derive(Message, Debug, Clone)]
@@ -22,12 +29,26 @@ pub enum ToServer {
 pub enum FromPlayer {
     /// Create new game
     CreateGame(String, usize),
+    /// Create new game with a seed or a turn timer
+    CreateCustomGame(GameOptions),
     /// Connect to some game
     Connect(ConnectInfo),
     /// Perform some action in the game
     Action(PlayerAction),
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct GameOptions {
+    pub name: String,
+    pub player_count: usize,
+    /// The seed that determines the random choices of the game, random if not given
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The time limit of every turn in seconds, no limit if not given
+    #[serde(default)]
+    pub turn_seconds: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct GameInfo {
     pub name: String,
@@ -53,5 +74,12 @@ pub struct ActiveGameInfo {
     pub name: String,
     pub player_count: usize,
     pub connected_players: Vec<String>,
-    pub game: Option<Game>,
+    /// The game as seen by the recipient
+    pub game: Option<PlayerView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreInfo {
+    pub player_name: String,
+    pub score: Score,
 }
\ No newline at end of file
@@ -0,0 +1,231 @@
+use crate::game::{ResourceAllocation, ResourceArray};
+
+/// Find the cheapest allocation that produces exactly the needed resources, or None if they can't be produced.
+/// `productions` and `costs` are indexed by relative player position, the player's own productions being free.
+/// Among allocations of the same cost, the one buying the fewest resources from the neighbours is chosen.
+pub fn cheapest_allocation(productions: &[Vec<ResourceArray>; 3], costs: &[ResourceArray; 3], needed: ResourceArray) -> Option<ResourceAllocation> {
+    minimal_allocations(productions, costs, needed).into_iter()
+        .min_by_key(|option| (option.payment[0] + option.payment[1], option.bought))
+        .map(|option| option.allocation)
+}
+
+/// Find one allocation for every distinct way to pay the neighbours that is not more expensive than another one
+/// for both neighbours at the same time.
+pub fn payment_options(productions: &[Vec<ResourceArray>; 3], costs: &[ResourceArray; 3], needed: ResourceArray) -> Vec<ResourceAllocation> {
+    minimal_allocations(productions, costs, needed).into_iter()
+        .map(|option| option.allocation)
+        .collect()
+}
+
+/// An allocation with the gold paid to the (left, right) neighbours and the number of resources bought from them.
+struct PaymentOption {
+    allocation: ResourceAllocation,
+    payment: [u32; 2],
+    bought: u32,
+}
+
+impl PaymentOption {
+    /// Whether this option is at least as cheap as the given payment for both neighbours, and buys at most as many resources.
+    fn dominates(&self, payment: [u32; 2], bought: u32) -> bool {
+        self.payment[0] <= payment[0] && self.payment[1] <= payment[1] && self.bought <= bought
+    }
+}
+
+/// Enumerate the allocations that produce exactly the needed resources, keeping only one for every payment
+/// that no other allocation dominates.
+/// Productions of a single resource are interchangeable, so only the number of units bought from every player matters,
+/// and the player's own units are always used first since they are free.
+fn minimal_allocations(productions: &[Vec<ResourceArray>; 3], costs: &[ResourceArray; 3], needed: ResourceArray) -> Vec<PaymentOption> {
+    let choices: Vec<_> = (0..3)
+        .flat_map(|player| (0..productions[player].len()).map(move |production| (player, production)))
+        .filter(|&(player, production)| produced_resources(productions[player][production]).len() > 1)
+        .collect();
+    let mut search = Search {
+        productions,
+        costs,
+        choices: &choices,
+        allocation: [
+            vec![0; productions[0].len()],
+            vec![0; productions[1].len()],
+            vec![0; productions[2].len()],
+        ],
+        options: vec![],
+    };
+    search.explore_choices(0, needed);
+    search.options
+}
+
+/// The resources that can be chosen from a production.
+fn produced_resources(production: ResourceArray) -> Vec<usize> {
+    (0..7).filter(|&resource| production[resource] > 0).collect()
+}
+
+/// The single resource of a production, if it doesn't offer a choice.
+fn single_resource(production: ResourceArray) -> Option<usize> {
+    match produced_resources(production)[..] {
+        [resource] => Some(resource),
+        _ => None,
+    }
+}
+
+struct Search<'a> {
+    productions: &'a [Vec<ResourceArray>; 3],
+    costs: &'a [ResourceArray; 3],
+    /// The (player, production) pairs that produce one of several resources.
+    choices: &'a [(usize, usize)],
+    /// The allocation being built.
+    allocation: ResourceAllocation,
+    /// The complete allocations found so far that no other one dominates.
+    options: Vec<PaymentOption>,
+}
+
+impl<'a> Search<'a> {
+    /// Try every resource for every choice production, including not using it.
+    fn explore_choices(&mut self, choice: usize, needed: ResourceArray) {
+        if self.is_dominated() {
+            return;
+        }
+        if choice == self.choices.len() {
+            self.explore_units(0, needed);
+            return;
+        }
+        let (player, production) = self.choices[choice];
+        self.explore_choices(choice + 1, needed);
+        for resource in produced_resources(self.productions[player][production]) {
+            if needed[resource] > 0 {
+                let mut needed = needed;
+                needed[resource] -= 1;
+                self.allocation[player][production] = resource + 1;
+                self.explore_choices(choice + 1, needed);
+            }
+        }
+        self.allocation[player][production] = 0;
+    }
+
+    /// Cover the remaining resources with single productions: own units first, then every split between the neighbours.
+    fn explore_units(&mut self, resource: usize, needed: ResourceArray) {
+        if self.is_dominated() {
+            return;
+        }
+        if resource == 7 {
+            self.record();
+            return;
+        }
+        let own = self.take_units(1, resource, needed[resource]);
+        let remaining = needed[resource] - own.len() as u32;
+        let available = |player: usize| self.productions[player].iter().enumerate()
+            .filter(|&(production, &produced)| single_resource(produced) == Some(resource) && self.allocation[player][production] == 0)
+            .count() as u32;
+        let (left_available, right_available) = (available(0), available(2));
+        for from_left in 0..=remaining.min(left_available) {
+            if remaining - from_left > right_available {
+                continue;
+            }
+            let left = self.take_units(0, resource, from_left);
+            let right = self.take_units(2, resource, remaining - from_left);
+            self.explore_units(resource + 1, needed);
+            self.release_units(2, &right);
+            self.release_units(0, &left);
+        }
+        self.release_units(1, &own);
+    }
+
+    /// Allocate up to `count` free single productions of a player to a resource, and return them.
+    fn take_units(&mut self, player: usize, resource: usize, count: u32) -> Vec<usize> {
+        let taken: Vec<_> = (0..self.productions[player].len())
+            .filter(|&production| single_resource(self.productions[player][production]) == Some(resource) && self.allocation[player][production] == 0)
+            .take(count as usize)
+            .collect();
+        for &production in &taken {
+            self.allocation[player][production] = resource + 1;
+        }
+        taken
+    }
+
+    fn release_units(&mut self, player: usize, productions: &[usize]) {
+        for &production in productions {
+            self.allocation[player][production] = 0;
+        }
+    }
+
+    /// The gold paid to the (left, right) neighbours and the number of resources bought from them by the current allocation.
+    fn payment(&self) -> ([u32; 2], u32) {
+        let mut payment = [0; 2];
+        let mut bought = 0;
+        for (side, &player) in [0, 2].iter().enumerate() {
+            for &chosen in &self.allocation[player] {
+                if chosen > 0 {
+                    payment[side] += self.costs[player][chosen - 1];
+                    bought += 1;
+                }
+            }
+        }
+        (payment, bought)
+    }
+
+    /// Whether a complete allocation already found is not more expensive than the current one.
+    /// Completing the allocation can only buy more, so the branch can be pruned.
+    fn is_dominated(&self) -> bool {
+        let (payment, bought) = self.payment();
+        self.options.iter().any(|option| option.dominates(payment, bought))
+    }
+
+    fn record(&mut self) {
+        let (payment, bought) = self.payment();
+        self.options.retain(|option| !(payment[0] <= option.payment[0] && payment[1] <= option.payment[1] && bought <= option.bought));
+        self.options.push(PaymentOption {
+            allocation: self.allocation.clone(),
+            payment,
+            bought,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COSTS: [ResourceArray; 3] = [[2; 7], [0; 7], [2; 7]];
+
+    #[test]
+    fn prefers_own_production() {
+        let productions = [vec![[1, 0, 0, 0, 0, 0, 0]], vec![[1, 0, 0, 0, 0, 0, 0]], vec![]];
+        let allocation = cheapest_allocation(&productions, &COSTS, [1, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(allocation, [vec![0], vec![1], vec![]]);
+    }
+
+    #[test]
+    fn prefers_cheaper_neighbour() {
+        let productions = [vec![[0, 1, 0, 0, 0, 0, 0]], vec![], vec![[0, 1, 0, 0, 0, 0, 0]]];
+        let costs = [[2; 7], [0; 7], [1, 1, 1, 1, 2, 2, 2]];
+        let allocation = cheapest_allocation(&productions, &costs, [0, 1, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(allocation, [vec![0], vec![], vec![2]]);
+    }
+
+    #[test]
+    fn assigns_choices_to_cover_everything() {
+        // The clay/wood choice must give the wood, since the clay/stone choice can only give the clay
+        let productions = [vec![], vec![[1, 1, 0, 0, 0, 0, 0], [1, 0, 0, 1, 0, 0, 0]], vec![]];
+        let allocation = cheapest_allocation(&productions, &COSTS, [1, 1, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(allocation, [vec![], vec![2, 1], vec![]]);
+    }
+
+    #[test]
+    fn missing_resources() {
+        let productions = [vec![[1, 0, 0, 0, 0, 0, 0]], vec![], vec![]];
+        assert!(cheapest_allocation(&productions, &COSTS, [2, 0, 0, 0, 0, 0, 0]).is_none());
+        assert_eq!(cheapest_allocation(&productions, &COSTS, [0; 7]).unwrap(), [vec![0], vec![], vec![]]);
+    }
+
+    #[test]
+    fn distinct_payment_options() {
+        // Two wood can be bought 2-0, 1-1 or 0-2 from the neighbours
+        let productions = [vec![[0, 1, 0, 0, 0, 0, 0]; 2], vec![], vec![[0, 1, 0, 0, 0, 0, 0]; 2]];
+        let options = payment_options(&productions, &COSTS, [0, 2, 0, 0, 0, 0, 0]);
+        assert_eq!(options.len(), 3);
+        // Buying is never better than using an own choice production
+        let productions = [vec![[0, 1, 0, 0, 0, 0, 0]], vec![[1, 1, 0, 0, 0, 0, 0]], vec![]];
+        let options = payment_options(&productions, &COSTS, [0, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(options, vec![[vec![0], vec![2], vec![]]]);
+    }
+}
@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use crate::game::{CardEffect, PlayerData, COLOR_PURPLE};
+
+/// The final score of a player, by category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub military: i32,
+    pub treasury: i32,
+    pub wonder: i32,
+    pub civilian: i32,
+    pub scientific: i32,
+    pub commercial: i32,
+    pub guilds: i32,
+    pub total: i32,
+    /// The rank of the player, 0 being the winner. Players with the same points are ranked by gold.
+    pub rank: usize,
+}
+
+/// Compute the final scores of all players.
+pub fn compute_scores(players: &[PlayerData]) -> Vec<Score> {
+    let mut scores: Vec<_> = (0..players.len()).map(|i| score_player(players, i)).collect();
+    let keys: Vec<_> = scores.iter().zip(players).map(|(score, player)| (score.total, player.gold)).collect();
+    for (i, score) in scores.iter_mut().enumerate() {
+        score.rank = keys.iter().filter(|&&key| key > keys[i]).count();
+    }
+    scores
+}
+
+fn score_player(players: &[PlayerData], i: usize) -> Score {
+    use CardEffect::*;
+
+    let player = &players[i];
+    let neighbours = [&players[(i + players.len() - 1) % players.len()], &players[(i + 1) % players.len()]];
+    let mut science_symbols = [0; 3];
+    let mut science_wildcards = 0;
+    let mut score = Score {
+        military: player.military_tokens.iter().sum(),
+        treasury: (player.gold / 3) as i32,
+        wonder: 0,
+        civilian: 0,
+        scientific: 0,
+        commercial: 0,
+        guilds: 0,
+        total: 0,
+        rank: 0,
+    };
+
+    for card in &player.board_cards {
+        match card.effect {
+            Points(points) => score.civilian += points as i32,
+            GoldAndPointsPerCard(color, _, points) => score.commercial += (player.count_cards(color) * points) as i32,
+            GoldAndPointsPerWonderStage(_, points) => score.commercial += (player.wonder_stages_built as u32 * points) as i32,
+            _ => (),
+        }
+    }
+    let guilds = player.board_cards.iter().filter(|card| card.color == COLOR_PURPLE).chain(player.copied_guild.iter());
+    for effect in guilds.map(|card| &card.effect) {
+        score.guilds += match *effect {
+            PointsPerNeighbourCard(color, points) => {
+                neighbours.iter().map(|neighbour| neighbour.count_cards(color) * points).sum::<u32>() as i32
+            },
+            PointsPerNeighbourDefeat(points) => {
+                let defeats = neighbours.iter().flat_map(|neighbour| &neighbour.military_tokens).filter(|&&token| token < 0).count();
+                defeats as i32 * points as i32
+            },
+            PointsPerWonderStageWithNeighbours(points) => {
+                let stages = player.wonder_stages_built + neighbours.iter().map(|neighbour| neighbour.wonder_stages_built).sum::<usize>();
+                stages as i32 * points as i32
+            },
+            PointsPerOwnCard(ref colors, points) => {
+                colors.iter().map(|&color| player.count_cards(color) * points).sum::<u32>() as i32
+            },
+            PointsForCompleteWonder(points) if player.wonder_stages_built == player.wonder.stages.len() => points as i32,
+            _ => 0,
+        }
+    }
+    for stage in &player.wonder.stages[..player.wonder_stages_built] {
+        for effect in &stage.effects {
+            if let Points(points) = effect {
+                score.wonder += *points as i32;
+            }
+        }
+    }
+    for effect in player.built_effects() {
+        match effect {
+            Science(symbol) => science_symbols[*symbol] += 1,
+            AnyScience => science_wildcards += 1,
+            _ => (),
+        }
+    }
+    score.scientific = science_points(science_symbols, science_wildcards) as i32;
+
+    score.total = score.military + score.treasury + score.wonder + score.civilian + score.scientific + score.commercial + score.guilds;
+    score
+}
+
+/// Compute the points given by science symbols: the square of the count of each symbol, plus 7 points per complete set.
+/// Each wildcard counts as the symbol that maximizes the points.
+pub fn science_points(symbols: [u32; 3], wildcards: u32) -> u32 {
+    if wildcards > 0 {
+        (0..3).map(|symbol| {
+            let mut symbols = symbols;
+            symbols[symbol] += 1;
+            science_points(symbols, wildcards - 1)
+        }).max().unwrap()
+    } else {
+        let complete_sets = symbols.iter().min().unwrap();
+        symbols.iter().map(|count| count * count).sum::<u32>() + 7 * complete_sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Card, Wonder, WonderSide, WonderStage, COLOR_BLUE, COLOR_BROWN, COLOR_GRAY, COLOR_RED, COLOR_YELLOW};
+
+    fn card(color: usize, effect: CardEffect) -> Card {
+        Card {
+            color,
+            name: format!("{:?}", effect),
+            gold_cost: 0,
+            resource_cost: [0; 7],
+            effect,
+            chaining_targets: vec![],
+            chaining_sources: vec![],
+        }
+    }
+
+    fn cards(count: usize, color: usize) -> Vec<Card> {
+        vec![card(color, CardEffect::Shields(0)); count]
+    }
+
+    /// Four players without gold, with a wonder of three stages without effects.
+    fn players() -> Vec<PlayerData> {
+        let wonder = Wonder {
+            name: "Test".to_owned(),
+            side: WonderSide::A,
+            starting_resource: [0; 7],
+            stages: vec![WonderStage { resource_cost: [0; 7], effects: vec![] }; 3],
+        };
+        (0..4).map(|_| {
+            let mut player = PlayerData::new(wonder.clone());
+            player.gold = 0;
+            player
+        }).collect()
+    }
+
+    fn guild_points(players: &[PlayerData]) -> i32 {
+        compute_scores(players)[0].guilds
+    }
+
+    #[test]
+    fn science_without_wildcards() {
+        assert_eq!(science_points([0, 0, 0], 0), 0);
+        assert_eq!(science_points([1, 1, 1], 0), 10);
+        assert_eq!(science_points([3, 2, 1], 0), 21);
+        assert_eq!(science_points([4, 0, 0], 0), 16);
+        assert_eq!(science_points([2, 2, 2], 0), 26);
+    }
+
+    #[test]
+    fn wildcard_completes_a_set() {
+        // [2, 2, 2] beats [3, 2, 1]
+        assert_eq!(science_points([2, 2, 1], 1), 26);
+    }
+
+    #[test]
+    fn wildcard_stacks_on_a_single_symbol() {
+        // [5, 0, 0] beats [4, 1, 0]
+        assert_eq!(science_points([4, 0, 0], 1), 25);
+        assert_eq!(science_points([0, 0, 0], 1), 1);
+    }
+
+    #[test]
+    fn two_wildcards() {
+        // [2, 1, 1] or [1, 1, 2] beat [3, 1, 0] and [2, 2, 0]
+        assert_eq!(science_points([1, 1, 0], 2), 13);
+        // [5, 1, 0] beats [4, 1, 1] and [3, 2, 1]
+        assert_eq!(science_points([3, 1, 0], 2), 26);
+        assert_eq!(science_points([0, 0, 0], 2), 4);
+    }
+
+    #[test]
+    fn guild_counts_neighbour_cards() {
+        // Player 0 is between players 3 and 1, player 2 is not a neighbour
+        let mut players = players();
+        players[0].board_cards = cards(5, COLOR_BROWN);
+        players[0].board_cards.push(card(COLOR_PURPLE, CardEffect::PointsPerNeighbourCard(COLOR_BROWN, 1)));
+        players[1].board_cards = cards(2, COLOR_BROWN);
+        players[2].board_cards = cards(4, COLOR_BROWN);
+        players[3].board_cards = cards(1, COLOR_BROWN);
+        players[3].board_cards.extend(cards(1, COLOR_GRAY));
+        assert_eq!(guild_points(&players), 3);
+    }
+
+    #[test]
+    fn guild_counts_neighbour_defeats() {
+        let mut players = players();
+        players[0].board_cards.push(card(COLOR_PURPLE, CardEffect::PointsPerNeighbourDefeat(1)));
+        players[0].military_tokens = vec![-1, -1];
+        players[1].military_tokens = vec![-1, 3, -1];
+        players[2].military_tokens = vec![-1];
+        players[3].military_tokens = vec![1, -1];
+        assert_eq!(guild_points(&players), 3);
+    }
+
+    #[test]
+    fn guild_counts_own_and_neighbour_wonder_stages() {
+        let mut players = players();
+        players[0].board_cards.push(card(COLOR_PURPLE, CardEffect::PointsPerWonderStageWithNeighbours(1)));
+        players[0].wonder_stages_built = 2;
+        players[1].wonder_stages_built = 3;
+        players[2].wonder_stages_built = 3;
+        players[3].wonder_stages_built = 1;
+        assert_eq!(guild_points(&players), 6);
+    }
+
+    #[test]
+    fn guild_counts_own_cards_including_guilds() {
+        let mut players = players();
+        players[0].board_cards = cards(2, COLOR_BROWN);
+        players[0].board_cards.extend(cards(1, COLOR_GRAY));
+        players[0].board_cards.extend(cards(3, COLOR_RED));
+        players[0].board_cards.push(card(COLOR_PURPLE, CardEffect::PointsPerNeighbourDefeat(1)));
+        players[0].board_cards.push(card(COLOR_PURPLE, CardEffect::PointsPerOwnCard(vec![COLOR_BROWN, COLOR_GRAY, COLOR_PURPLE], 1)));
+        players[1].board_cards = cards(4, COLOR_BROWN);
+        // The shipowners count themselves
+        assert_eq!(guild_points(&players), 5);
+    }
+
+    #[test]
+    fn guild_needs_a_complete_wonder() {
+        let mut players = players();
+        players[0].board_cards.push(card(COLOR_PURPLE, CardEffect::PointsForCompleteWonder(7)));
+        players[0].wonder_stages_built = 2;
+        assert_eq!(guild_points(&players), 0);
+        players[0].wonder_stages_built = 3;
+        assert_eq!(guild_points(&players), 7);
+    }
+
+    #[test]
+    fn copied_guild_is_scored_for_the_copying_player() {
+        let mut players = players();
+        let guild = card(COLOR_PURPLE, CardEffect::PointsPerNeighbourCard(COLOR_BROWN, 1));
+        players[0].copied_guild = Some(guild.clone());
+        players[1].board_cards = cards(2, COLOR_BROWN);
+        players[1].board_cards.push(guild);
+        players[3].board_cards = cards(1, COLOR_BROWN);
+        assert_eq!(guild_points(&players), 3);
+        // The owner of the guild still scores it, with its own neighbours
+        assert_eq!(compute_scores(&players)[1].guilds, 0);
+    }
+
+    #[test]
+    fn commercial_cards_score_own_cards_and_stages() {
+        let mut players = players();
+        players[0].board_cards = cards(3, COLOR_BROWN);
+        players[0].board_cards.push(card(COLOR_YELLOW, CardEffect::GoldAndPointsPerCard(COLOR_BROWN, 1, 1)));
+        players[0].board_cards.push(card(COLOR_YELLOW, CardEffect::GoldAndPointsPerCard(COLOR_YELLOW, 1, 1)));
+        players[0].board_cards.push(card(COLOR_YELLOW, CardEffect::GoldAndPointsPerWonderStage(3, 1)));
+        players[0].wonder_stages_built = 2;
+        // The neighbours' cards only give gold when the card is played
+        players[1].board_cards = cards(4, COLOR_BROWN);
+        assert_eq!(compute_scores(&players)[0].commercial, 3 + 3 + 2);
+    }
+
+    #[test]
+    fn ties_are_broken_by_gold() {
+        let mut players = players();
+        // 5 points each for players 0, 1 and 2, but player 1 has more gold left
+        players[0].gold = 3;
+        players[0].board_cards.push(card(COLOR_BLUE, CardEffect::Points(4)));
+        players[1].gold = 5;
+        players[1].board_cards.push(card(COLOR_BLUE, CardEffect::Points(4)));
+        players[2].gold = 3;
+        players[2].board_cards.push(card(COLOR_BLUE, CardEffect::Points(4)));
+        players[3].board_cards.push(card(COLOR_BLUE, CardEffect::Points(7)));
+        let scores = compute_scores(&players);
+        let totals: Vec<_> = scores.iter().map(|score| score.total).collect();
+        let ranks: Vec<_> = scores.iter().map(|score| score.rank).collect();
+        assert_eq!(totals, vec![5, 5, 5, 7]);
+        // Players with the same points and gold share their rank
+        assert_eq!(ranks, vec![2, 1, 2, 0]);
+    }
+}
@@ -0,0 +1,26 @@
+use rand::prelude::*;
+use crate::game::Game;
+
+/// Let every player that can play pick a random legal action, and tell whether someone played.
+pub fn play_random_round(game: &mut Game, rng: &mut impl Rng) -> bool {
+    let mut someone_played = false;
+    for player in 0..game.player_count() {
+        if let Some(legal_action) = game.legal_actions(player).choose(rng) {
+            for action in legal_action.player_actions() {
+                game.perform_action(player, action).unwrap();
+            }
+            someone_played = true;
+        }
+    }
+    someone_played
+}
+
+/// Play a whole game with random legal actions.
+pub fn play_random_game(player_count: usize, seed: u64) -> Game {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::new(player_count, seed);
+    while game.get_scores().is_none() {
+        play_random_round(&mut game, &mut rng);
+    }
+    game
+}
@@ -0,0 +1,135 @@
+use rand::prelude::*;
+use crate::game::{
+    CardEffect,
+    ResourceArray,
+    Wonder,
+    WonderSide,
+    WonderStage,
+    RIGHT_PLAYER,
+    LEFT_PLAYER,
+};
+
+/// The WonderRegistry produces the wonders for the games depending on the number of players.
+#[derive(Debug, Clone)]
+pub struct WonderRegistry {
+    wonders: Vec<[Wonder; 2]>,
+}
+
+impl WonderRegistry {
+    /// Randomly choose a different wonder for every player, each with a random side.
+    pub fn generate_wonders<R: Rng + ?Sized>(&self, player_count: usize, rng: &mut R) -> Vec<Wonder> {
+        let mut wonders: Vec<_> = self.wonders.choose_multiple(rng, player_count)
+            .map(|sides| sides[rng.gen_range(0, 2)].clone())
+            .collect();
+        wonders.shuffle(rng);
+        wonders
+    }
+
+    pub fn new() -> WonderRegistry {
+        use CardEffect::*;
+        use WonderSide::*;
+        Self {
+            wonders: vec![
+                [
+                    WonderBuilder::new("Colosse de Rhodes", A, [0, 0, 1, 0, 0, 0, 0])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![Points(3)])
+                        .with_stage([3, 0, 0, 0, 0, 0, 0], vec![Shields(2)])
+                        .with_stage([0, 0, 4, 0, 0, 0, 0], vec![Points(7)]),
+                    WonderBuilder::new("Colosse de Rhodes", B, [0, 0, 1, 0, 0, 0, 0])
+                        .with_stage([0, 0, 0, 3, 0, 0, 0], vec![Shields(1), Points(3), Gold(3)])
+                        .with_stage([0, 0, 4, 0, 0, 0, 0], vec![Shields(1), Points(4), Gold(4)]),
+                ],
+                [
+                    WonderBuilder::new("Phare d'Alexandrie", A, [0, 0, 0, 0, 1, 0, 0])
+                        .with_stage([0, 0, 0, 2, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 0, 2, 0, 0, 0, 0], vec![PrivateResources([1, 1, 1, 1, 0, 0, 0])])
+                        .with_stage([0, 0, 0, 0, 2, 0, 0], vec![Points(7)]),
+                    WonderBuilder::new("Phare d'Alexandrie", B, [0, 0, 0, 0, 1, 0, 0])
+                        .with_stage([2, 0, 0, 0, 0, 0, 0], vec![PrivateResources([1, 1, 1, 1, 0, 0, 0])])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![PrivateResources([0, 0, 0, 0, 1, 1, 1])])
+                        .with_stage([0, 0, 0, 3, 0, 0, 0], vec![Points(7)]),
+                ],
+                [
+                    WonderBuilder::new("Temple d'Artémis à Éphèse", A, [0, 0, 0, 0, 0, 1, 0])
+                        .with_stage([0, 0, 0, 2, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![Gold(9)])
+                        .with_stage([0, 0, 0, 0, 0, 2, 0], vec![Points(7)]),
+                    WonderBuilder::new("Temple d'Artémis à Éphèse", B, [0, 0, 0, 0, 0, 1, 0])
+                        .with_stage([0, 0, 0, 2, 0, 0, 0], vec![Points(2), Gold(4)])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![Points(3), Gold(4)])
+                        .with_stage([0, 0, 0, 0, 1, 1, 1], vec![Points(5), Gold(4)]),
+                ],
+                [
+                    WonderBuilder::new("Jardins suspendus de Babylone", A, [1, 0, 0, 0, 0, 0, 0])
+                        .with_stage([2, 0, 0, 0, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 3, 0, 0, 0, 0, 0], vec![AnyScience])
+                        .with_stage([4, 0, 0, 0, 0, 0, 0], vec![Points(7)]),
+                    WonderBuilder::new("Jardins suspendus de Babylone", B, [1, 0, 0, 0, 0, 0, 0])
+                        .with_stage([1, 0, 0, 0, 0, 0, 1], vec![Points(3)])
+                        .with_stage([0, 2, 0, 0, 1, 0, 0], vec![PlaySeventhCard])
+                        .with_stage([3, 0, 0, 0, 0, 1, 0], vec![AnyScience]),
+                ],
+                [
+                    WonderBuilder::new("Statue de Zeus à Olympie", A, [0, 1, 0, 0, 0, 0, 0])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 0, 0, 2, 0, 0, 0], vec![FreeBuildOncePerAge])
+                        .with_stage([0, 0, 2, 0, 0, 0, 0], vec![Points(7)]),
+                    WonderBuilder::new("Statue de Zeus à Olympie", B, [0, 1, 0, 0, 0, 0, 0])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![RawMaterialsCost(LEFT_PLAYER), RawMaterialsCost(RIGHT_PLAYER)])
+                        .with_stage([0, 0, 0, 2, 0, 0, 0], vec![Points(5)])
+                        .with_stage([0, 0, 2, 0, 0, 0, 1], vec![CopyGuild]),
+                ],
+                [
+                    WonderBuilder::new("Mausolée d'Halicarnasse", A, [0, 0, 0, 0, 0, 0, 1])
+                        .with_stage([2, 0, 0, 0, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 0, 3, 0, 0, 0, 0], vec![BuildFromDiscard])
+                        .with_stage([0, 0, 0, 0, 0, 0, 2], vec![Points(7)]),
+                    WonderBuilder::new("Mausolée d'Halicarnasse", B, [0, 0, 0, 0, 0, 0, 1])
+                        .with_stage([0, 0, 2, 0, 0, 0, 0], vec![Points(2), BuildFromDiscard])
+                        .with_stage([3, 0, 0, 0, 0, 0, 0], vec![Points(1), BuildFromDiscard])
+                        .with_stage([0, 0, 0, 0, 1, 1, 1], vec![BuildFromDiscard]),
+                ],
+                [
+                    WonderBuilder::new("Pyramides de Gizeh", A, [0, 0, 0, 1, 0, 0, 0])
+                        .with_stage([0, 0, 0, 2, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 3, 0, 0, 0, 0, 0], vec![Points(5)])
+                        .with_stage([0, 0, 0, 4, 0, 0, 0], vec![Points(7)]),
+                    WonderBuilder::new("Pyramides de Gizeh", B, [0, 0, 0, 1, 0, 0, 0])
+                        .with_stage([0, 2, 0, 0, 0, 0, 0], vec![Points(3)])
+                        .with_stage([0, 0, 0, 3, 0, 0, 0], vec![Points(5)])
+                        .with_stage([3, 0, 0, 0, 0, 0, 0], vec![Points(5)])
+                        .with_stage([0, 0, 0, 4, 0, 1, 0], vec![Points(7)]),
+                ],
+            ].into_iter().map(|[a, b]| [a.build(), b.build()]).collect(),
+        }
+    }
+}
+
+struct WonderBuilder {
+    wonder: Wonder,
+}
+
+impl WonderBuilder {
+    pub fn new<S: ToString>(name: S, side: WonderSide, starting_resource: ResourceArray) -> WonderBuilder {
+        Self {
+            wonder: Wonder {
+                name: name.to_string(),
+                side,
+                starting_resource,
+                stages: vec![],
+            }
+        }
+    }
+
+    pub fn with_stage(mut self, resource_cost: ResourceArray, effects: Vec<CardEffect>) -> Self {
+        self.wonder.stages.push(WonderStage {
+            resource_cost,
+            effects,
+        });
+        self
+    }
+
+    pub fn build(self) -> Wonder {
+        self.wonder
+    }
+}
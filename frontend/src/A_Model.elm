@@ -54,6 +54,28 @@ type alias GameModel =
     , connectedPlayers : List String
     , game : Maybe Game
     , shownPlayer : Int
+    , lastError : Maybe String
+    , remainingTime : Maybe Int
+    , scores : Maybe (List ScoreInfo)
+    }
+
+
+type alias ScoreInfo =
+    { playerName : String
+    , score : Score
+    }
+
+
+type alias Score =
+    { military : Int
+    , treasury : Int
+    , wonder : Int
+    , civilian : Int
+    , scientific : Int
+    , commercial : Int
+    , guilds : Int
+    , total : Int
+    , rank : Int
     }
 
 
@@ -115,11 +137,26 @@ emptyResourceArray =
 
 type CardEffect
     = Resources ResourceArray
+    | PrivateResources ResourceArray
     | Points Int
     | RawMaterialsCost Int
     | ManufacturedProductsCost
     | Shields Int
     | Science Int
+    | GoldPerCard CardColor Int
+    | Gold Int
+    | GoldAndPointsPerCard CardColor Int Int
+    | GoldAndPointsPerWonderStage Int Int
+    | PointsPerNeighbourCard CardColor Int
+    | PointsPerNeighbourDefeat Int
+    | PointsPerWonderStageWithNeighbours Int
+    | PointsPerOwnCard (List CardColor) Int
+    | PointsForCompleteWonder Int
+    | AnyScience
+    | FreeBuildOncePerAge
+    | BuildFromDiscard
+    | PlaySeventhCard
+    | CopyGuild
 
 
 type Play
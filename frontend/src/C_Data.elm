@@ -1,6 +1,6 @@
 module C_Data exposing (..)
 
-import A_Model exposing (Card, Game, GameInfo, Play, PlayerAction)
+import A_Model exposing (Card, Game, GameInfo, Play, PlayerAction, ScoreInfo)
 
 
 
@@ -12,6 +12,9 @@ import A_Model exposing (Card, Game, GameInfo, Play, PlayerAction)
 type ToPlayer
     = GameList (List GameInfo)
     | ActiveGame PlayerInfo ActiveGameInfo
+    | GameOver (List ScoreInfo)
+    | Error String
+    | RemainingTime Int
 
 
 type alias PlayerInfo =
@@ -14,6 +14,8 @@ import A_Model
         , ResourceAllocation
         , ResourceAllocationVerdict
         , ResourceArray
+        , Score
+        , ScoreInfo
         )
 import C_Data exposing (ActiveGameInfo, PlayerInfo, ToPlayer(..))
 import Json.Decode as Decode exposing (Decoder, at, index, int, list, maybe, string, succeed)
@@ -48,6 +50,9 @@ toPlayer =
                 (index 0 playerInfo)
                 (index 1 activeGameInfo)
             )
+        , at [ "GameOver" ] (Decode.map GameOver (list scoreInfo))
+        , at [ "Error" ] (Decode.map Error string)
+        , at [ "RemainingTime" ] (Decode.map RemainingTime int)
         ]
 
 
@@ -120,11 +125,26 @@ cardEffect : Decoder CardEffect
 cardEffect =
     Decode.oneOf
         [ at [ "Resources" ] (Decode.map Resources resourceArray)
+        , at [ "PrivateResources" ] (Decode.map PrivateResources resourceArray)
         , at [ "Points" ] (Decode.map Points int)
         , at [ "RawMaterialsCost" ] (Decode.map RawMaterialsCost int)
         , exact "ManufacturedProductsCost" string |> Decode.map (always ManufacturedProductsCost)
         , at [ "Shields" ] (Decode.map Shields int)
         , at [ "Science" ] (Decode.map Science int)
+        , at [ "GoldPerCard" ] (Decode.map2 GoldPerCard (index 0 cardColor) (index 1 int))
+        , at [ "Gold" ] (Decode.map Gold int)
+        , at [ "GoldAndPointsPerCard" ] (Decode.map3 GoldAndPointsPerCard (index 0 cardColor) (index 1 int) (index 2 int))
+        , at [ "GoldAndPointsPerWonderStage" ] (Decode.map2 GoldAndPointsPerWonderStage (index 0 int) (index 1 int))
+        , at [ "PointsPerNeighbourCard" ] (Decode.map2 PointsPerNeighbourCard (index 0 cardColor) (index 1 int))
+        , at [ "PointsPerNeighbourDefeat" ] (Decode.map PointsPerNeighbourDefeat int)
+        , at [ "PointsPerWonderStageWithNeighbours" ] (Decode.map PointsPerWonderStageWithNeighbours int)
+        , at [ "PointsPerOwnCard" ] (Decode.map2 PointsPerOwnCard (index 0 (list cardColor)) (index 1 int))
+        , at [ "PointsForCompleteWonder" ] (Decode.map PointsForCompleteWonder int)
+        , exact "AnyScience" string |> Decode.map (always AnyScience)
+        , exact "FreeBuildOncePerAge" string |> Decode.map (always FreeBuildOncePerAge)
+        , exact "BuildFromDiscard" string |> Decode.map (always BuildFromDiscard)
+        , exact "PlaySeventhCard" string |> Decode.map (always PlaySeventhCard)
+        , exact "CopyGuild" string |> Decode.map (always CopyGuild)
         ]
 
 
@@ -182,6 +202,29 @@ resourceAllocation =
     list (list int)
 
 
+scoreInfo : Decoder ScoreInfo
+scoreInfo =
+    Decode.map2 ScoreInfo
+        (at [ "player_name" ] string)
+        (at [ "score" ] score)
+
+
+score : Decoder Score
+score =
+    Decode.map2 (<|)
+        (Decode.map8 Score
+            (at [ "military" ] int)
+            (at [ "treasury" ] int)
+            (at [ "wonder" ] int)
+            (at [ "civilian" ] int)
+            (at [ "scientific" ] int)
+            (at [ "commercial" ] int)
+            (at [ "guilds" ] int)
+            (at [ "total" ] int)
+        )
+        (at [ "rank" ] int)
+
+
 resourceAllocationVerdict : Decoder ResourceAllocationVerdict
 resourceAllocationVerdict =
     Decode.map3 ResourceAllocationVerdict
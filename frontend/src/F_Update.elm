@@ -44,7 +44,16 @@ update msg model =
                                     ( InLobby { lobbyModel | games = list }, Cmd.none )
 
                                 ActiveGame playerInfo activeGameInfo ->
-                                    updateActiveGame Nothing Nothing playerInfo activeGameInfo <| Cmd.batch [ loadRenderParameters, loadTextures ]
+                                    updateActiveGame Nothing playerInfo activeGameInfo <| Cmd.batch [ loadRenderParameters, loadTextures ]
+
+                                GameOver _ ->
+                                    noUpdate
+
+                                Error _ ->
+                                    noUpdate
+
+                                RemainingTime _ ->
+                                    noUpdate
 
                 NewGame m ->
                     handleNewGameMessage m lobbyModel
@@ -84,7 +93,16 @@ update msg model =
                                     noUpdate
 
                                 ActiveGame playerInfo activeGameInfo ->
-                                    updateActiveGame gameModel.renderParameters gameModel.textures playerInfo activeGameInfo Cmd.none
+                                    updateActiveGame (Just gameModel) playerInfo activeGameInfo Cmd.none
+
+                                GameOver scores ->
+                                    ( InGame { gameModel | scores = Just scores, remainingTime = Nothing }, Cmd.none )
+
+                                Error error ->
+                                    ( InGame { gameModel | lastError = Just error }, Cmd.none )
+
+                                RemainingTime seconds ->
+                                    ( InGame { gameModel | remainingTime = Just seconds }, Cmd.none )
 
                 NewGame _ ->
                     noUpdate
@@ -96,7 +114,7 @@ update msg model =
                     noUpdate
 
                 PerformAction action ->
-                    ( model
+                    ( InGame { gameModel | lastError = Nothing }
                     , Action action
                         |> encodeFromPlayer
                         |> send
@@ -124,7 +142,7 @@ update msg model =
                 CenterShownPlayer ->
                     ( InGame { gameModel | shownPlayer = gameModel.playerId }, Cmd.none )
 
-        updateActiveGame renderParameters textures playerInfo activeGameInfo cmd =
+        updateActiveGame previousGameModel playerInfo activeGameInfo cmd =
             let
                 playerId =
                     activeGameInfo.connectedPlayers
@@ -132,8 +150,8 @@ update msg model =
                         |> Maybe.withDefault -1
             in
             ( InGame
-                { renderParameters = renderParameters
-                , textures = textures
+                { renderParameters = Maybe.andThen .renderParameters previousGameModel
+                , textures = Maybe.andThen .textures previousGameModel
                 , gameName = activeGameInfo.name
                 , playerName = playerInfo.playerName
                 , playerId = playerId
@@ -143,6 +161,9 @@ update msg model =
                 , connectedPlayers = activeGameInfo.connectedPlayers
                 , game = activeGameInfo.game
                 , shownPlayer = playerId
+                , lastError = Maybe.andThen .lastError previousGameModel
+                , remainingTime = Maybe.andThen .remainingTime previousGameModel
+                , scores = Maybe.andThen .scores previousGameModel
                 }
             , cmd
             )
@@ -13,6 +13,7 @@ import A_Model
         , PlayerData
         , RenderParameters
         , ResourceArray
+        , ScoreInfo
         , emptyResourceArray
         , isVerdictOk
         )
@@ -73,10 +74,48 @@ viewGame gameModel =
         [ h2 [] [ text ("Welcome to game " ++ gameModel.gameName) ]
         , viewGameStatus
         , viewConnectedPlayers gameModel.connectedPlayers
+        , viewServerMessages gameModel
         , maybeViewActiveGame
         ]
 
 
+viewServerMessages : GameModel -> Html Msg
+viewServerMessages gameModel =
+    div [] <|
+        List.filterMap identity
+            [ Maybe.map (\error -> p [] [ text ("Action refusée : " ++ error) ]) gameModel.lastError
+            , Maybe.map (\seconds -> p [] [ text ("Temps restant : " ++ String.fromInt seconds ++ " s") ]) gameModel.remainingTime
+            , Maybe.map viewScores gameModel.scores
+            ]
+
+
+viewScores : List ScoreInfo -> Html Msg
+viewScores scores =
+    let
+        headers =
+            [ "Joueur", "Militaire", "Trésor", "Merveille", "Civil", "Science", "Commerce", "Guildes", "Total", "Rang" ]
+
+        viewScore scoreInfo =
+            tr [] <|
+                List.map (\cell -> td [] [ text cell ])
+                    [ scoreInfo.playerName
+                    , String.fromInt scoreInfo.score.military
+                    , String.fromInt scoreInfo.score.treasury
+                    , String.fromInt scoreInfo.score.wonder
+                    , String.fromInt scoreInfo.score.civilian
+                    , String.fromInt scoreInfo.score.scientific
+                    , String.fromInt scoreInfo.score.commercial
+                    , String.fromInt scoreInfo.score.guilds
+                    , String.fromInt scoreInfo.score.total
+                    , String.fromInt (scoreInfo.score.rank + 1)
+                    ]
+    in
+    div []
+        [ h3 [] [ text "Scores finaux" ]
+        , table [] (tr [] (List.map (\columnName -> th [] [ text columnName ]) headers) :: List.map viewScore scores)
+        ]
+
+
 nothing : Html Msg
 nothing =
     div [] []